@@ -1,4 +1,10 @@
-use clap::Parser;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+use clap::{Parser, Subcommand};
+use ccpclean::clock::{parse_duration, parse_interval};
+use ccpclean::killer::Signal;
+use ccpclean::serve;
 
 #[derive(Parser, Debug)]
 #[command(
@@ -9,14 +15,155 @@ use clap::Parser;
 )]
 pub struct Cli {
     /// Loose mode: show all processes listening on local ports (default: strict - dev runtimes only)
-    #[arg(short = 'a', long = "all")]
+    #[arg(short = 'a', long = "all", global = true)]
     pub all: bool,
 
     /// Filter by specific port
-    #[arg(short = 'p', long = "port")]
+    #[arg(short = 'p', long = "port", global = true)]
     pub port: Option<u16>,
 
+    /// Only show processes idle (no connections, ~0% CPU) for at least this long, e.g. 2h
+    #[arg(long = "idle", global = true, value_parser = parse_duration, value_name = "DURATION")]
+    pub idle: Option<Duration>,
+
     /// Non-interactive: print list and exit
     #[arg(long = "no-tui")]
     pub no_tui: bool,
+
+    /// Signal the kill action sends instead of SIGTERM-then-SIGKILL: term, kill, int, hup, stop
+    /// or cont. With --no-tui, it is sent to every listed process
+    #[arg(long = "signal", value_parser = parse_signal, value_name = "SIGNAL")]
+    pub signal: Option<Signal>,
+
+    /// Config file (default: ~/.config/ccpclean/config.toml)
+    #[arg(long = "config", global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Rescan periodically and print servers that appeared, disappeared or changed ports
+    Watch {
+        /// Time between scans, e.g. 10s, 5m, 1h
+        #[arg(short = 'i', long = "interval", default_value = "10s", value_parser = parse_interval)]
+        interval: Duration,
+    },
+    /// Rescan periodically and kill processes matching the configured reaping policy
+    Daemon {
+        /// Time between scans (overrides `daemon.interval` in the config)
+        #[arg(short = 'i', long = "interval", value_parser = parse_interval)]
+        interval: Option<Duration>,
+
+        /// Log what would be killed without killing anything
+        #[arg(long = "dry-run")]
+        dry_run: bool,
+
+        /// Action log file (default: ~/.local/state/ccpclean/daemon.log)
+        #[arg(long = "log-file", value_name = "PATH")]
+        log_file: Option<PathBuf>,
+    },
+    /// Show past kill attempts (filter with --port, --name, --since, --until)
+    History {
+        /// Only kills at or after this time: YYYY-MM-DD or a duration ago, e.g. 2d
+        #[arg(long = "since", value_name = "WHEN")]
+        since: Option<String>,
+
+        /// Only kills before this time: YYYY-MM-DD or a duration ago, e.g. 1h
+        #[arg(long = "until", value_name = "WHEN")]
+        until: Option<String>,
+
+        /// Only processes whose name contains this (case-insensitive)
+        #[arg(short = 'n', long = "name")]
+        name: Option<String>,
+    },
+    /// Relaunch a killed process with its original command, directory and environment
+    Restart {
+        /// Entry ID from `ccpclean history`
+        id: usize,
+    },
+    /// Serve the Model Context Protocol over stdio so coding agents can list and kill servers
+    Mcp {
+        /// Never kill anything, even when a tool call asks to
+        #[arg(long = "dry-run")]
+        dry_run: bool,
+    },
+    /// Serve scan results, score breakdowns and kill actions as a local JSON API for editor plugins
+    Serve {
+        /// Loopback address to listen on
+        #[arg(long = "listen", value_name = "ADDR", default_value = serve::DEFAULT_LISTEN)]
+        listen: SocketAddr,
+
+        /// Listen on this Unix socket instead of TCP
+        #[arg(long = "socket", value_name = "PATH", conflicts_with = "listen")]
+        socket: Option<PathBuf>,
+
+        /// Never kill anything, even when a request asks to
+        #[arg(long = "dry-run")]
+        dry_run: bool,
+    },
+}
+
+/// Parses a signal name such as `stop`, `HUP` or `SIGINT`.
+pub fn parse_signal(s: &str) -> Result<Signal, String> {
+    Signal::from_name(s)
+        .ok_or_else(|| format!("unknown signal '{}': use term, kill, int, hup, stop or cont", s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watch_subcommand() {
+        let cli = Cli::parse_from(["ccpclean", "watch", "--interval", "3s", "--all"]);
+        assert!(cli.all);
+        match cli.command {
+            Some(Command::Watch { interval }) => assert_eq!(interval, Duration::from_secs(3)),
+            other => panic!("unexpected command: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_daemon_subcommand() {
+        let cli = Cli::parse_from(["ccpclean", "daemon", "--dry-run", "--config", "/tmp/c.toml"]);
+        assert_eq!(cli.config, Some(PathBuf::from("/tmp/c.toml")));
+        match cli.command {
+            Some(Command::Daemon { interval, dry_run, log_file }) => {
+                assert_eq!(interval, None);
+                assert!(dry_run);
+                assert_eq!(log_file, None);
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_mcp_subcommand() {
+        let cli = Cli::parse_from(["ccpclean", "mcp", "--dry-run"]);
+        assert!(matches!(cli.command, Some(Command::Mcp { dry_run: true })));
+    }
+
+    #[test]
+    fn test_serve_subcommand() {
+        let cli = Cli::parse_from(["ccpclean", "serve"]);
+        match cli.command {
+            Some(Command::Serve { listen, socket, dry_run }) => {
+                assert_eq!(listen.to_string(), serve::DEFAULT_LISTEN);
+                assert_eq!(socket, None);
+                assert!(!dry_run);
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+        assert!(Cli::try_parse_from(["ccpclean", "serve", "--listen", "127.0.0.1:9000", "--socket", "/tmp/s"]).is_err());
+    }
+
+    #[test]
+    fn test_signal_option() {
+        let cli = Cli::parse_from(["ccpclean", "--no-tui", "--signal", "SIGSTOP"]);
+        assert_eq!(cli.signal, Some(Signal::Stop));
+        assert!(Cli::try_parse_from(["ccpclean", "--signal", "usr1"]).is_err());
+    }
 }
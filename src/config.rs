@@ -0,0 +1,348 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use serde::{Deserialize, Deserializer};
+use crate::clock::{parse_duration, parse_interval};
+use crate::paths;
+use crate::process_info::ProcessInfo;
+#[cfg(feature = "tui")]
+use crate::tui::keys::{Keymap, KeymapError};
+#[cfg(feature = "tui")]
+use crate::tui::theme::{self, Theme, ThemeError};
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, toml::de::Error),
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Io(path, e) => write!(f, "Failed to read config {}: {}", path.display(), e),
+            ConfigError::Parse(path, e) => write!(f, "Invalid config {}: {}", path.display(), e),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub protect: Protect,
+    pub daemon: DaemonConfig,
+    /// TUI key overrides: action name -> keys, e.g. `kill_selected = ["Enter", "x"]`.
+    pub keys: BTreeMap<String, Vec<String>>,
+    pub theme: ThemeConfig,
+    pub clipboard: ClipboardConfig,
+}
+
+/// Processes that must never be killed automatically.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Protect {
+    /// Exact process names, case-insensitive.
+    pub names: Vec<String>,
+    pub ports: Vec<u16>,
+    /// Substrings matched against the full command line.
+    pub commands: Vec<String>,
+}
+
+impl Protect {
+    pub fn is_protected(&self, p: &ProcessInfo) -> bool {
+        if self.names.iter().any(|n| n.eq_ignore_ascii_case(&p.name)) {
+            return true;
+        }
+        if p.ports.iter().any(|port| self.ports.contains(port)) {
+            return true;
+        }
+        let cmd = p.cmd.join(" ");
+        self.commands.iter().any(|c| cmd.contains(c.as_str()))
+    }
+}
+
+/// TUI colors: a built-in theme plus optional per-element colors.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeConfig {
+    /// `dark`, `light`, `high-contrast` or `monochrome`.
+    pub name: Option<String>,
+    pub header: Option<String>,
+    pub selected: Option<String>,
+    pub muted: Option<String>,
+    pub accent: Option<String>,
+    pub group: Option<String>,
+    pub score_high: Option<String>,
+    pub score_medium: Option<String>,
+    pub score_low: Option<String>,
+    pub protected: Option<String>,
+    pub system: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DaemonConfig {
+    #[serde(deserialize_with = "de_interval")]
+    pub interval: Duration,
+    pub dry_run: bool,
+    pub log_file: Option<PathBuf>,
+    pub policy: Policy,
+}
+
+impl Default for DaemonConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(300),
+            dry_run: false,
+            log_file: None,
+            policy: Policy::default(),
+        }
+    }
+}
+
+/// What happens to values copied in the TUI.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClipboardConfig {
+    /// Print everything copied during the session when the TUI exits, for terminals that
+    /// ignore OSC 52. Turn off to keep copied values out of the scrollback.
+    pub print_on_exit: bool,
+}
+
+impl Default for ClipboardConfig {
+    fn default() -> Self {
+        Self { print_on_exit: true }
+    }
+}
+
+/// A process is reaped only when it satisfies every condition of the policy.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Policy {
+    pub min_score: u8,
+    pub orphaned_only: bool,
+    #[serde(deserialize_with = "de_opt_duration")]
+    pub min_uptime: Option<Duration>,
+    /// No established connections and ~0% CPU for at least this long.
+    #[serde(deserialize_with = "de_opt_duration")]
+    pub min_idle: Option<Duration>,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self {
+            min_score: 80,
+            orphaned_only: true,
+            min_uptime: Some(Duration::from_secs(2 * 3600)),
+            min_idle: None,
+        }
+    }
+}
+
+impl Policy {
+    pub fn matches(&self, p: &ProcessInfo) -> bool {
+        if p.score < self.min_score {
+            return false;
+        }
+        if self.orphaned_only && !p.is_orphaned() {
+            return false;
+        }
+        if let Some(min) = self.min_uptime {
+            if p.uptime() < min {
+                return false;
+            }
+        }
+        if let Some(min) = self.min_idle {
+            if p.idle().is_none_or(|idle| idle < min) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+fn de_interval<'de, D: Deserializer<'de>>(d: D) -> Result<Duration, D::Error> {
+    let s = String::deserialize(d)?;
+    parse_interval(&s).map_err(serde::de::Error::custom)
+}
+
+fn de_opt_duration<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Duration>, D::Error> {
+    Option::<String>::deserialize(d)?
+        .map(|s| parse_duration(&s).map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+impl Config {
+    pub fn parse(path: &Path, text: &str) -> Result<Self, ConfigError> {
+        toml::from_str(text).map_err(|e| ConfigError::Parse(path.to_path_buf(), e))
+    }
+
+    /// The configured theme, or monochrome under `NO_COLOR`. Like the keymap, it
+    /// is checked only when the TUI starts.
+    #[cfg(feature = "tui")]
+    pub fn theme(&self) -> Result<Theme, ThemeError> {
+        Theme::from_config(&self.theme, theme::no_color())
+    }
+
+    /// The TUI keymap. Checked only when the TUI starts, so a bad `[keys]` table never
+    /// blocks the other commands.
+    #[cfg(feature = "tui")]
+    pub fn keymap(&self) -> Result<Keymap, KeymapError> {
+        Keymap::from_config(&self.keys)
+    }
+
+    /// Loads `path`, or the default config file if none is given. A missing default file
+    /// yields the built-in defaults; a missing explicit file is an error.
+    pub fn load(path: Option<&Path>) -> Result<Self, ConfigError> {
+        let (path, explicit) = match path {
+            Some(p) => (p.to_path_buf(), true),
+            None => match paths::config_file() {
+                Some(p) => (p, false),
+                None => return Ok(Config::default()),
+            },
+        };
+        match fs::read_to_string(&path) {
+            Ok(text) => Config::parse(&path, &text),
+            Err(e) if e.kind() == io::ErrorKind::NotFound && !explicit => Ok(Config::default()),
+            Err(e) => Err(ConfigError::Io(path, e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn make_proc(name: &str, ports: Vec<u16>, score: u8, parent_pid: Option<u32>) -> ProcessInfo {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        ProcessInfo {
+            pid: 42,
+            name: name.to_string(),
+            cmd: vec![name.to_string(), "server.js".to_string()],
+            ports,
+            start_time_secs: now - 3 * 3600,
+            memory_kb: 0,
+            parent_pid,
+            parent_name: parent_pid.map(|_| "init".to_string()),
+            is_dev_runtime: true,
+            score,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_full_config() {
+        let text = r#"
+            [protect]
+            names = ["postgres"]
+            ports = [5432]
+            commands = ["keep-me"]
+
+            [daemon]
+            interval = "30s"
+            dry_run = true
+
+            [daemon.policy]
+            min_score = 70
+            orphaned_only = false
+            min_uptime = "1h"
+            min_idle = "2h"
+
+            [clipboard]
+            print_on_exit = false
+        "#;
+        let cfg = Config::parse(Path::new("test.toml"), text).unwrap();
+        assert_eq!(cfg.protect.names, vec!["postgres"]);
+        assert_eq!(cfg.daemon.interval, Duration::from_secs(30));
+        assert!(cfg.daemon.dry_run);
+        assert_eq!(cfg.daemon.policy.min_score, 70);
+        assert!(!cfg.daemon.policy.orphaned_only);
+        assert_eq!(cfg.daemon.policy.min_uptime, Some(Duration::from_secs(3600)));
+        assert_eq!(cfg.daemon.policy.min_idle, Some(Duration::from_secs(7200)));
+        assert!(!cfg.clipboard.print_on_exit);
+    }
+
+    #[test]
+    fn test_parse_empty_uses_defaults() {
+        let cfg = Config::parse(Path::new("test.toml"), "").unwrap();
+        assert_eq!(cfg.daemon.interval, Duration::from_secs(300));
+        assert_eq!(cfg.daemon.policy.min_score, 80);
+        assert!(cfg.daemon.policy.orphaned_only);
+        assert!(cfg.clipboard.print_on_exit);
+    }
+
+    #[cfg(feature = "tui")]
+    #[test]
+    fn test_parse_key_overrides() {
+        let cfg = Config::parse(Path::new("test.toml"), "[keys]\nkill_selected = [\"Enter\", \"x\"]\n").unwrap();
+        assert!(cfg.keymap().unwrap().is_overridden(crate::tui::keys::Action::KillSelected));
+        // Conflicts do not fail the load; they surface when the TUI asks for the keymap.
+        let cfg = Config::parse(Path::new("test.toml"), "[keys]\nquit = [\"j\"]\n").unwrap();
+        let err = cfg.keymap().unwrap_err();
+        assert!(err.to_string().contains("bound to both `move_down` and `quit`"));
+    }
+
+    #[cfg(feature = "tui")]
+    #[test]
+    fn test_parse_theme() {
+        let cfg = Config::parse(Path::new("test.toml"), "[theme]\nname = \"high-contrast\"\nselected = \"blue\"\n").unwrap();
+        assert_eq!(cfg.theme.name.as_deref(), Some("high-contrast"));
+        assert!(cfg.theme().is_ok());
+        let cfg = Config::parse(Path::new("test.toml"), "[theme]\nname = \"neon\"\n").unwrap();
+        assert!(cfg.theme().is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_keys_and_bad_durations() {
+        assert!(Config::parse(Path::new("t"), "[daemon]\nintervall = \"5m\"").is_err());
+        assert!(Config::parse(Path::new("t"), "[daemon]\ninterval = \"soon\"").is_err());
+        assert!(Config::parse(Path::new("t"), "[daemon]\ninterval = \"0s\"").is_err());
+    }
+
+    #[test]
+    fn test_protect_matches_name_port_and_command() {
+        let protect = Protect {
+            names: vec!["Node".to_string()],
+            ports: vec![8080],
+            commands: vec!["worker".to_string()],
+        };
+        assert!(protect.is_protected(&make_proc("node", vec![3000], 90, None)));
+        assert!(protect.is_protected(&make_proc("python", vec![8080], 90, None)));
+        let mut p = make_proc("ruby", vec![4000], 90, None);
+        assert!(!protect.is_protected(&p));
+        p.cmd.push("--worker".to_string());
+        assert!(protect.is_protected(&p));
+    }
+
+    #[test]
+    fn test_policy_matches() {
+        let policy = Policy::default();
+        assert!(policy.matches(&make_proc("node", vec![3000], 90, Some(1))));
+        assert!(!policy.matches(&make_proc("node", vec![3000], 60, Some(1))));
+
+        let mut young = make_proc("node", vec![3000], 90, Some(1));
+        young.start_time_secs += 3 * 3600 - 60;
+        assert!(!policy.matches(&young));
+
+        let mut attached = make_proc("node", vec![3000], 90, Some(500));
+        attached.parent_name = Some("zsh".to_string());
+        assert!(!policy.matches(&attached));
+    }
+
+    #[test]
+    fn test_policy_min_idle() {
+        let policy = Policy {
+            min_idle: Some(Duration::from_secs(2 * 3600)),
+            ..Default::default()
+        };
+        let mut p = make_proc("node", vec![3000], 90, Some(1));
+        assert!(!policy.matches(&p), "unknown idleness must not match");
+        p.idle_secs = Some(3600);
+        assert!(!policy.matches(&p));
+        p.idle_secs = Some(3 * 3600);
+        assert!(policy.matches(&p));
+    }
+}
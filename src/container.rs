@@ -0,0 +1,498 @@
+#[cfg(feature = "containers")]
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+#[cfg(feature = "containers")]
+use serde::Deserialize;
+use crate::process_info::ProcessInfo;
+
+/// Processes that forward a published port into a container. The port belongs to the
+/// container, not to them.
+const PROXIES: &[&str] = &[
+    "docker-proxy", "rootlessport", "rootlessport-child", "slirp4netns", "pasta", "gvproxy",
+    "vpnkit", "com.docker.backend",
+];
+
+/// Daemons that run containers; killing one takes every container it manages down with it.
+const RUNTIMES: &[&str] = &[
+    "dockerd", "containerd", "containerd-shim", "containerd-shim-runc-v2", "podman", "conmon",
+];
+
+/// Engine API calls are local and fast; a socket that does not answer within this is ignored.
+#[cfg(feature = "containers")]
+const LIST_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// `docker stop` waits up to 10 seconds for the container before killing it.
+const STOP_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[cfg(feature = "containers")]
+const COMPOSE_PROJECT_LABEL: &str = "com.docker.compose.project";
+
+/// How long a container listing is reused. watch, the daemon and serve scan repeatedly, and a
+/// long-lived docker-proxy would otherwise mean an Engine API call on every scan.
+const CACHE_TTL: Duration = Duration::from_secs(30);
+
+static CACHE: Mutex<Option<(Instant, Vec<Container>)>> = Mutex::new(None);
+
+pub fn is_proxy(name: &str) -> bool {
+    PROXIES.contains(&name)
+}
+
+pub fn is_runtime(name: &str) -> bool {
+    RUNTIMES.contains(&name)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Runtime {
+    Docker,
+    Podman,
+}
+
+impl Runtime {
+    pub fn name(self) -> &'static str {
+        match self {
+            Runtime::Docker => "Docker",
+            Runtime::Podman => "Podman",
+        }
+    }
+}
+
+/// The container that owns a process's ports.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Container {
+    pub id: String,
+    /// Without an Engine API socket this is the short ID.
+    pub name: String,
+    pub image: Option<String>,
+    pub compose_project: Option<String>,
+    pub runtime: Runtime,
+    /// Host ports the container publishes.
+    pub ports: Vec<u16>,
+    /// The Engine API socket the container was found through; needed to stop it.
+    pub socket: Option<PathBuf>,
+}
+
+impl Container {
+    /// `web (nginx:1.25, compose project shop)`.
+    pub fn display(&self) -> String {
+        let details: Vec<String> = self
+            .image
+            .iter()
+            .cloned()
+            .chain(self.compose_project.iter().map(|p| format!("compose project {}", p)))
+            .collect();
+        if details.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{} ({})", self.name, details.join(", "))
+        }
+    }
+
+    /// Stopping goes through the Engine API, so containers seen only in `/proc` cannot be.
+    pub fn can_stop(&self) -> bool {
+        self.socket.is_some()
+    }
+}
+
+#[derive(Debug)]
+pub enum ContainerError {
+    NoSocket(String),
+    Io(io::Error),
+    /// A non-success HTTP status with the API's error message.
+    Api(u16, String),
+}
+
+impl fmt::Display for ContainerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContainerError::NoSocket(name) => write!(f, "No Docker/Podman socket to stop container {}", name),
+            ContainerError::Io(e) => write!(f, "Container API request failed: {}", e),
+            ContainerError::Api(status, message) => write!(f, "Container API returned {}: {}", status, message),
+        }
+    }
+}
+
+impl From<io::Error> for ContainerError {
+    fn from(e: io::Error) -> Self {
+        ContainerError::Io(e)
+    }
+}
+
+#[cfg(feature = "containers")]
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ApiContainer {
+    id: String,
+    #[serde(default)]
+    names: Vec<String>,
+    image: Option<String>,
+    labels: Option<HashMap<String, String>>,
+    ports: Option<Vec<ApiPort>>,
+}
+
+#[cfg(feature = "containers")]
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ApiPort {
+    public_port: Option<u16>,
+}
+
+#[cfg(feature = "containers")]
+#[derive(Deserialize)]
+struct ApiError {
+    message: String,
+}
+
+fn unix_path(host: &str) -> Option<PathBuf> {
+    host.strip_prefix("unix://").map(PathBuf::from)
+}
+
+/// Engine API sockets to ask: `$DOCKER_HOST` (or the default Docker locations) and
+/// `$CONTAINER_HOST` (or the rootless and rootful Podman locations). Only unix sockets are
+/// supported; a TCP `DOCKER_HOST` is skipped.
+fn sockets() -> Vec<(Runtime, PathBuf)> {
+    let mut candidates = Vec::new();
+    match env::var("DOCKER_HOST") {
+        Ok(host) => candidates.extend(unix_path(&host).map(|p| (Runtime::Docker, p))),
+        Err(_) => {
+            candidates.push((Runtime::Docker, PathBuf::from("/var/run/docker.sock")));
+            if let Some(home) = env::var_os("HOME") {
+                candidates.push((Runtime::Docker, Path::new(&home).join(".docker/run/docker.sock")));
+            }
+        }
+    }
+    match env::var("CONTAINER_HOST") {
+        Ok(host) => candidates.extend(unix_path(&host).map(|p| (Runtime::Podman, p))),
+        Err(_) => {
+            if let Some(dir) = env::var_os("XDG_RUNTIME_DIR") {
+                candidates.push((Runtime::Podman, Path::new(&dir).join("podman/podman.sock")));
+            }
+            candidates.push((Runtime::Podman, PathBuf::from("/run/podman/podman.sock")));
+        }
+    }
+    let mut seen = Vec::new();
+    candidates.retain(|(_, path)| {
+        let keep = path.exists() && !seen.contains(path);
+        seen.push(path.clone());
+        keep
+    });
+    candidates
+}
+
+/// Splits a raw HTTP response into status code and body.
+fn parse_response(raw: &str) -> io::Result<(u16, String)> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "malformed HTTP response");
+    let (head, body) = raw.split_once("\r\n\r\n").ok_or_else(invalid)?;
+    let status = head
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(invalid)?;
+    Ok((status, body.to_string()))
+}
+
+/// One HTTP/1.0 request over a unix socket. HTTP/1.0 keeps the response unchunked and has
+/// the server close the connection when it is done.
+#[cfg(unix)]
+fn request(socket: &Path, method: &str, path: &str, timeout: Duration) -> io::Result<(u16, String)> {
+    use std::io::{Read, Write};
+    use std::os::unix::net::UnixStream;
+
+    let mut stream = UnixStream::connect(socket)?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+    write!(stream, "{} {} HTTP/1.0\r\nHost: localhost\r\nContent-Length: 0\r\n\r\n", method, path)?;
+    let mut raw = String::new();
+    stream.read_to_string(&mut raw)?;
+    parse_response(&raw)
+}
+
+#[cfg(not(unix))]
+fn request(_socket: &Path, _method: &str, _path: &str, _timeout: Duration) -> io::Result<(u16, String)> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "unix sockets are not supported on this platform"))
+}
+
+#[cfg(feature = "containers")]
+fn api_error(status: u16, body: &str) -> ContainerError {
+    let message = serde_json::from_str::<ApiError>(body)
+        .map(|e| e.message)
+        .unwrap_or_else(|_| body.trim().to_string());
+    ContainerError::Api(status, message)
+}
+
+/// Without the JSON parser, the body is the message.
+#[cfg(not(feature = "containers"))]
+fn api_error(status: u16, body: &str) -> ContainerError {
+    ContainerError::Api(status, body.trim().to_string())
+}
+
+#[cfg(feature = "containers")]
+fn parse_containers(body: &str, runtime: Runtime, socket: &Path) -> serde_json::Result<Vec<Container>> {
+    let api: Vec<ApiContainer> = serde_json::from_str(body)?;
+    Ok(api
+        .into_iter()
+        .map(|c| {
+            let mut ports: Vec<u16> = c.ports.unwrap_or_default().iter().filter_map(|p| p.public_port).collect();
+            ports.sort_unstable();
+            ports.dedup();
+            Container {
+                name: c.names.first().map(|n| n.trim_start_matches('/').to_string()).unwrap_or_else(|| short_id(&c.id)),
+                id: c.id,
+                image: c.image.filter(|i| !i.is_empty()),
+                compose_project: c.labels.and_then(|mut l| l.remove(COMPOSE_PROJECT_LABEL)),
+                runtime,
+                ports,
+                socket: Some(socket.to_path_buf()),
+            }
+        })
+        .collect())
+}
+
+/// Running containers known to the Engine API at `socket`.
+#[cfg(feature = "containers")]
+pub fn list(runtime: Runtime, socket: &Path) -> Result<Vec<Container>, ContainerError> {
+    let (status, body) = request(socket, "GET", "/containers/json", LIST_TIMEOUT)?;
+    if status != 200 {
+        return Err(api_error(status, &body));
+    }
+    parse_containers(&body, runtime, socket)
+        .map_err(|e| ContainerError::Io(io::Error::new(io::ErrorKind::InvalidData, e)))
+}
+
+#[cfg(not(feature = "containers"))]
+pub fn list(_runtime: Runtime, _socket: &Path) -> Result<Vec<Container>, ContainerError> {
+    Err(ContainerError::Io(io::Error::new(io::ErrorKind::Unsupported, "built without the `containers` feature")))
+}
+
+/// Stops the container the way `docker stop` does (SIGTERM, then SIGKILL after a timeout).
+pub fn stop(container: &Container) -> Result<(), ContainerError> {
+    let Some(ref socket) = container.socket else {
+        return Err(ContainerError::NoSocket(container.name.clone()));
+    };
+    let path = format!("/containers/{}/stop", container.id);
+    match request(socket, "POST", &path, STOP_TIMEOUT)? {
+        // 304: already stopped.
+        (204 | 304, _) => {
+            *CACHE.lock().unwrap_or_else(|e| e.into_inner()) = None;
+            Ok(())
+        }
+        (status, body) => Err(api_error(status, &body)),
+    }
+}
+
+fn short_id(id: &str) -> String {
+    id.chars().take(12).collect()
+}
+
+/// The container ID in a `/proc/<pid>/cgroup` file: cgroup v1 `/docker/<id>`, systemd's
+/// `docker-<id>.scope` and Podman's `libpod-<id>.scope`.
+fn cgroup_container(cgroup: &str) -> Option<(Runtime, String)> {
+    let is_id = |s: &str| s.len() == 64 && s.bytes().all(|b| b.is_ascii_hexdigit());
+    for line in cgroup.lines() {
+        let path = line.rsplit(':').next().unwrap_or("");
+        for segment in path.split('/') {
+            let segment = segment.trim_end_matches(".scope");
+            let (runtime, id) = if let Some(id) = segment.strip_prefix("libpod-conmon-").or_else(|| segment.strip_prefix("libpod-")) {
+                (Runtime::Podman, id)
+            } else {
+                (Runtime::Docker, segment.strip_prefix("docker-").unwrap_or(segment))
+            };
+            if is_id(id) {
+                return Some((runtime, id.to_string()));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn cgroup_of(pid: u32) -> Option<(Runtime, String)> {
+    cgroup_container(&std::fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()?)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn cgroup_of(_pid: u32) -> Option<(Runtime, String)> {
+    None
+}
+
+/// Containers of every reachable runtime, listed at most once per `CACHE_TTL`.
+fn containers() -> Vec<Container> {
+    let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((listed, ref containers)) = *cache {
+        if listed.elapsed() < CACHE_TTL {
+            return containers.clone();
+        }
+    }
+    let containers: Vec<Container> = sockets()
+        .into_iter()
+        .filter_map(|(runtime, socket)| list(runtime, &socket).ok())
+        .flatten()
+        .collect();
+    *cache = Some((Instant::now(), containers.clone()));
+    containers
+}
+
+/// Finds the container behind each listening process: port proxies by the host ports the
+/// container publishes, processes running inside a container (host networking) by their
+/// cgroup. The Engine APIs are only asked when there is something to resolve, and their
+/// answer is cached for `CACHE_TTL`.
+pub fn annotate(processes: &mut [ProcessInfo]) {
+    let cgroups: Vec<Option<(Runtime, String)>> = processes
+        .iter()
+        .map(|p| if p.ports.is_empty() || is_proxy(&p.name) { None } else { cgroup_of(p.pid) })
+        .collect();
+    let proxies = processes.iter().any(|p| is_proxy(&p.name) && !p.ports.is_empty());
+    if !proxies && cgroups.iter().all(Option::is_none) {
+        return;
+    }
+
+    let containers = containers();
+    for (p, cgroup) in processes.iter_mut().zip(cgroups) {
+        p.container = match cgroup {
+            Some((runtime, id)) => Some(containers.iter().find(|c| c.id == id).cloned().unwrap_or(Container {
+                name: short_id(&id),
+                id,
+                image: None,
+                compose_project: None,
+                runtime,
+                ports: Vec::new(),
+                socket: None,
+            })),
+            None if is_proxy(&p.name) => containers
+                .iter()
+                .find(|c| c.ports.iter().any(|port| p.ports.contains(port)))
+                .cloned(),
+            None => None,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: &str = "3f4e5d6c7b8a99887766554433221100ffeeddccbbaa00112233445566778899";
+
+    #[test]
+    fn test_cgroup_container() {
+        let v1 = format!("12:pids:/docker/{}\n0::/", ID);
+        assert_eq!(cgroup_container(&v1), Some((Runtime::Docker, ID.to_string())));
+        let systemd = format!("0::/system.slice/docker-{}.scope", ID);
+        assert_eq!(cgroup_container(&systemd), Some((Runtime::Docker, ID.to_string())));
+        let podman = format!("0::/user.slice/user-1000.slice/user@1000.service/user.slice/libpod-{}.scope/container", ID);
+        assert_eq!(cgroup_container(&podman), Some((Runtime::Podman, ID.to_string())));
+        assert_eq!(cgroup_container("0::/user.slice/user-1000.slice/session-2.scope"), None);
+    }
+
+    #[cfg(feature = "containers")]
+    #[test]
+    fn test_parse_response() {
+        let raw = "HTTP/1.0 404 Not Found\r\nContent-Type: application/json\r\n\r\n{\"message\":\"No such container\"}";
+        let (status, body) = parse_response(raw).unwrap();
+        assert_eq!(status, 404);
+        assert_eq!(api_error(status, &body).to_string(), "Container API returned 404: No such container");
+        assert!(parse_response("garbage").is_err());
+    }
+
+    #[test]
+    fn test_display() {
+        let mut c = Container {
+            id: ID.to_string(),
+            name: "web".to_string(),
+            image: Some("nginx:1.25".to_string()),
+            compose_project: Some("shop".to_string()),
+            runtime: Runtime::Docker,
+            ports: vec![8080],
+            socket: None,
+        };
+        assert_eq!(c.display(), "web (nginx:1.25, compose project shop)");
+        c.image = None;
+        c.compose_project = None;
+        assert_eq!(c.display(), "web");
+        assert!(!c.can_stop());
+    }
+
+    /// A fake Engine API on a unix socket: answers each connection with the next canned
+    /// response and hands back the request lines it received.
+    #[cfg(all(unix, feature = "containers"))]
+    fn fake_api(name: &str, responses: Vec<&'static str>) -> (PathBuf, std::thread::JoinHandle<Vec<String>>) {
+        use std::io::{BufRead, BufReader, Write};
+        use std::os::unix::net::UnixListener;
+
+        let path = env::temp_dir().join(format!("ccpclean-{}-{}.sock", std::process::id(), name));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let handle = std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                requests.push(line.trim_end().to_string());
+                loop {
+                    let mut header = String::new();
+                    if reader.read_line(&mut header).unwrap() == 0 || header == "\r\n" {
+                        break;
+                    }
+                }
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+            requests
+        });
+        (path, handle)
+    }
+
+    #[cfg(all(unix, feature = "containers"))]
+    #[test]
+    fn test_list_and_stop_through_fake_socket() {
+        let list_response = concat!(
+            "HTTP/1.0 200 OK\r\nContent-Type: application/json\r\n\r\n",
+            r#"[{"Id":"abc123","Names":["/shop-web-1"],"Image":"nginx:1.25","#,
+            r#""Labels":{"com.docker.compose.project":"shop"},"#,
+            r#""Ports":[{"IP":"0.0.0.0","PrivatePort":80,"PublicPort":8080,"Type":"tcp"},"#,
+            r#"{"IP":"::","PrivatePort":80,"PublicPort":8080,"Type":"tcp"},{"PrivatePort":443,"Type":"tcp"}]},"#,
+            r#"{"Id":"def456","Names":["/db"],"Image":"postgres","Labels":null,"Ports":[]}]"#,
+        );
+        let (socket, server) = fake_api("list", vec![list_response, "HTTP/1.0 204 No Content\r\n\r\n"]);
+
+        let containers = list(Runtime::Docker, &socket).unwrap();
+        assert_eq!(containers.len(), 2);
+        let web = &containers[0];
+        assert_eq!(web.name, "shop-web-1");
+        assert_eq!(web.ports, vec![8080]);
+        assert_eq!(web.compose_project.as_deref(), Some("shop"));
+        assert_eq!(containers[1].compose_project, None);
+
+        stop(web).unwrap();
+        let requests = server.join().unwrap();
+        let _ = std::fs::remove_file(&socket);
+        assert_eq!(requests, vec!["GET /containers/json HTTP/1.0", "POST /containers/abc123/stop HTTP/1.0"]);
+    }
+
+    #[cfg(all(unix, feature = "containers"))]
+    #[test]
+    fn test_stop_reports_api_error() {
+        let response = "HTTP/1.0 500 Internal Server Error\r\n\r\n{\"message\":\"permission denied\"}";
+        let (socket, server) = fake_api("stop", vec![response]);
+        let c = Container {
+            id: "abc123".to_string(),
+            name: "web".to_string(),
+            image: None,
+            compose_project: None,
+            runtime: Runtime::Podman,
+            ports: Vec::new(),
+            socket: Some(socket.clone()),
+        };
+        let err = stop(&c).unwrap_err();
+        server.join().unwrap();
+        let _ = std::fs::remove_file(&socket);
+        assert_eq!(err.to_string(), "Container API returned 500: permission denied");
+    }
+}
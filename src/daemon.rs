@@ -0,0 +1,188 @@
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::clock;
+use crate::config::{DaemonConfig, Policy, Protect};
+use crate::history::{self, Trigger};
+use crate::process_info::ProcessInfo;
+
+const TICK: Duration = Duration::from_millis(200);
+
+#[derive(Debug, PartialEq)]
+pub enum Decision {
+    Kill,
+    Protected,
+}
+
+/// Decides what to do with each scanned process. Processes outside the policy are left out.
+pub fn plan<'a>(
+    processes: &'a [ProcessInfo],
+    policy: &Policy,
+    protect: &Protect,
+    self_pid: u32,
+) -> Vec<(&'a ProcessInfo, Decision)> {
+    processes
+        .iter()
+        .filter(|p| p.pid != self_pid && policy.matches(p))
+        .map(|p| {
+            if protect.is_protected(p) {
+                (p, Decision::Protected)
+            } else {
+                (p, Decision::Kill)
+            }
+        })
+        .collect()
+}
+
+/// Appends timestamped lines to the log file (if any) and echoes them to stdout.
+pub struct ActionLog {
+    file: Option<File>,
+}
+
+impl ActionLog {
+    pub fn open(path: Option<&Path>) -> io::Result<Self> {
+        let file = match path {
+            Some(path) => {
+                if let Some(dir) = path.parent() {
+                    fs::create_dir_all(dir)?;
+                }
+                Some(OpenOptions::new().create(true).append(true).open(path)?)
+            }
+            None => None,
+        };
+        Ok(Self { file })
+    }
+
+    pub fn log(&mut self, msg: &str) {
+        let line = format!("{}  {}", clock::format_utc(clock::now_secs()), msg);
+        println!("{}", line);
+        if let Some(ref mut f) = self.file {
+            if let Err(e) = writeln!(f, "{}", line) {
+                eprintln!("Failed to write daemon log: {}", e);
+            }
+        }
+    }
+}
+
+fn describe(p: &ProcessInfo) -> String {
+    format!(
+        "{:<8} {:<12} :{:<16} score={:<3} {}",
+        p.pid,
+        p.name,
+        p.ports_display(),
+        p.score,
+        p.cmd.join(" ")
+    )
+}
+
+/// Rescans every `cfg.interval` and reaps processes matching the policy until SIGTERM/Ctrl-C.
+pub fn run<F>(cfg: &DaemonConfig, protect: &Protect, mut collect: F) -> io::Result<()>
+where
+    F: FnMut() -> Vec<ProcessInfo>,
+{
+    let stop = Arc::new(AtomicBool::new(false));
+    {
+        let stop = Arc::clone(&stop);
+        ctrlc::set_handler(move || stop.store(true, Ordering::SeqCst))
+            .map_err(io::Error::other)?;
+    }
+
+    let log_path = cfg
+        .log_file
+        .clone()
+        .or_else(|| crate::paths::state_dir().map(|d| d.join("daemon.log")));
+    let mut log = ActionLog::open(log_path.as_deref())?;
+
+    let policy = &cfg.policy;
+    log.log(&format!(
+        "daemon started: interval={}s dry_run={} min_score={} orphaned_only={} min_uptime={} min_idle={}",
+        cfg.interval.as_secs(),
+        cfg.dry_run,
+        policy.min_score,
+        policy.orphaned_only,
+        policy.min_uptime.map(|d| format!("{}s", d.as_secs())).unwrap_or_else(|| "none".to_string()),
+        policy.min_idle.map(|d| format!("{}s", d.as_secs())).unwrap_or_else(|| "none".to_string()),
+    ));
+
+    let self_pid = std::process::id();
+    // Protected matches are logged once per process rather than on every scan.
+    let mut reported_protected: HashSet<(u32, u64)> = HashSet::new();
+
+    while !stop.load(Ordering::SeqCst) {
+        let processes = collect();
+        for (p, decision) in plan(&processes, policy, protect, self_pid) {
+            match decision {
+                Decision::Protected => {
+                    if reported_protected.insert((p.pid, p.start_time_secs)) {
+                        log.log(&format!("protected  {}", describe(p)));
+                    }
+                }
+                Decision::Kill if cfg.dry_run => log.log(&format!("would-kill {}", describe(p))),
+                Decision::Kill => match history::kill(p, Trigger::Daemon) {
+                    Ok(_) => log.log(&format!("killed     {}", describe(p))),
+                    Err(e) => log.log(&format!("failed     {}  ({})", describe(p), e)),
+                },
+            }
+        }
+
+        let started = Instant::now();
+        while started.elapsed() < cfg.interval && !stop.load(Ordering::SeqCst) {
+            thread::sleep(TICK);
+        }
+    }
+
+    log.log("daemon stopped");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn make_proc(pid: u32, name: &str, score: u8) -> ProcessInfo {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        ProcessInfo {
+            pid,
+            name: name.to_string(),
+            cmd: vec![name.to_string()],
+            ports: vec![3000 + pid as u16],
+            start_time_secs: now - 5 * 3600,
+            memory_kb: 0,
+            parent_pid: Some(1),
+            parent_name: Some("init".to_string()),
+            is_dev_runtime: true,
+            score,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_plan_kills_matching_and_skips_protected() {
+        let processes = vec![
+            make_proc(1, "node", 90),
+            make_proc(2, "python", 40),
+            make_proc(3, "postgres", 90),
+        ];
+        let protect = Protect {
+            names: vec!["postgres".to_string()],
+            ..Default::default()
+        };
+        let plan = plan(&processes, &Policy::default(), &protect, 999);
+        assert_eq!(plan.len(), 2);
+        assert_eq!((plan[0].0.pid, &plan[0].1), (1, &Decision::Kill));
+        assert_eq!((plan[1].0.pid, &plan[1].1), (3, &Decision::Protected));
+    }
+
+    #[test]
+    fn test_plan_never_targets_self() {
+        let processes = vec![make_proc(7, "node", 100)];
+        let plan = plan(&processes, &Policy::default(), &Protect::default(), 7);
+        assert!(plan.is_empty());
+    }
+}
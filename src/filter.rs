@@ -12,39 +12,60 @@ const DEV_CMD_KEYWORDS: &[&str] = &[
     "http", "web", "watch",
 ];
 
+/// No connections and ~0% CPU for this long is the strongest orphan signal.
+const IDLE_SCORE_SECS: u64 = 3600;
+
+/// A process run by a systemd service was started on purpose and is not an orphan.
+const MANAGED_UNIT_PENALTY: i16 = 40;
+
 pub fn is_dev_runtime(name: &str) -> bool {
     let lower = name.to_lowercase();
     DEV_RUNTIMES.iter().any(|&r| lower == r || lower.starts_with(r))
 }
 
-pub fn compute_score(p: &ProcessInfo) -> u8 {
-    let mut score: u16 = 0;
+/// The signals behind `compute_score`, each with the points it adds (or takes away).
+pub fn score_factors(p: &ProcessInfo) -> Vec<(&'static str, i16)> {
+    let mut factors = Vec::new();
 
     if p.is_dev_runtime {
-        score += 30;
+        factors.push(("dev runtime", 30));
     }
 
-    if p.ports.iter().any(|&port| port >= 1024 && port <= 9999) {
-        score += 20;
+    if p.ports.iter().any(|&port| (1024..=9999).contains(&port)) {
+        factors.push(("listens on a typical dev port (1024-9999)", 20));
     }
 
     let cmd_str = p.cmd.join(" ").to_lowercase();
     if DEV_CMD_KEYWORDS.iter().any(|&kw| cmd_str.contains(kw)) {
-        score += 20;
+        factors.push(("command looks like a dev server", 20));
     }
 
     if let Some(ref parent) = p.parent_name {
         let parent_lower = parent.to_lowercase();
         if DEV_PARENT_NAMES.iter().any(|&pn| parent_lower.contains(pn)) {
-            score += 20;
+            factors.push(("started from a shell or agent", 20));
         }
     }
 
     if p.uptime().as_secs() > 1800 {
-        score += 10;
+        factors.push(("running for over 30 minutes", 10));
+    }
+
+    if p.idle_secs.is_some_and(|secs| secs >= IDLE_SCORE_SECS) {
+        factors.push(("idle for over an hour", 20));
     }
 
-    score.min(100) as u8
+    if p.unit.is_some() {
+        factors.push(("run by a systemd service", -MANAGED_UNIT_PENALTY));
+    }
+
+    factors
+}
+
+/// How likely `p` is to be a forgotten dev server, from 0 to 100.
+pub fn compute_score(p: &ProcessInfo) -> u8 {
+    let score: i16 = score_factors(p).iter().map(|(_, points)| points).sum();
+    score.clamp(0, 100) as u8
 }
 
 pub fn score_display(score: u8) -> String {
@@ -53,12 +74,40 @@ pub fn score_display(score: u8) -> String {
     format!("{}{}", "●".repeat(filled), "○".repeat(empty))
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScoreLevel {
+    High,
+    Medium,
+    Low,
+}
+
+impl ScoreLevel {
+    pub fn of(score: u8) -> Self {
+        if score >= 70 {
+            ScoreLevel::High
+        } else if score >= 40 {
+            ScoreLevel::Medium
+        } else {
+            ScoreLevel::Low
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ScoreLevel::High => "High",
+            ScoreLevel::Medium => "Medium",
+            ScoreLevel::Low => "Low",
+        }
+    }
+}
+
 #[derive(Clone, Copy, PartialEq)]
 pub enum FilterMode {
     Strict,
     Loose,
 }
 
+/// Keeps the listening processes `mode` shows: dev runtimes only, or all of them.
 pub fn apply_filter(processes: Vec<ProcessInfo>, mode: FilterMode) -> Vec<ProcessInfo> {
     processes
         .into_iter()
@@ -97,6 +146,7 @@ mod tests {
             parent_name: parent.map(|s| s.to_string()),
             is_dev_runtime: is_dev,
             score: 0,
+            ..Default::default()
         }
     }
 
@@ -136,6 +186,33 @@ mod tests {
         assert_eq!(p.score, 80);
     }
 
+    #[test]
+    fn test_score_idle_bonus() {
+        let mut p = make_proc("nginx", vec![80], vec!["nginx"], None);
+        p.idle_secs = Some(30 * 60);
+        assert_eq!(compute_score(&p), 10);
+        p.idle_secs = Some(2 * 3600);
+        assert_eq!(compute_score(&p), 30);
+    }
+
+    #[test]
+    fn test_score_managed_unit_penalty() {
+        let mut p = make_proc("node", vec![3000], vec!["node", "server.js"], Some("bash"));
+        p.unit = Some(crate::systemd::Unit { name: "dev-api.service".to_string(), user: true });
+        assert_eq!(compute_score(&p), 60);
+    }
+
+    #[test]
+    fn test_score_factors_add_up() {
+        let mut p = make_proc("node", vec![3000], vec!["node", "server.js"], Some("bash"));
+        p.unit = Some(crate::systemd::Unit { name: "dev-api.service".to_string(), user: true });
+        let factors = score_factors(&p);
+        assert!(factors.contains(&("dev runtime", 30)));
+        assert!(factors.contains(&("run by a systemd service", -40)));
+        let total: i16 = factors.iter().map(|(_, points)| points).sum();
+        assert_eq!(total, compute_score(&p) as i16);
+    }
+
     #[test]
     fn test_score_display() {
         assert_eq!(score_display(100), "●●●●●");
@@ -167,4 +244,12 @@ mod tests {
         let result = apply_filter(vec![p], FilterMode::Strict);
         assert!(result.is_empty());
     }
+
+    #[test]
+    fn test_score_level_thresholds() {
+        assert_eq!(ScoreLevel::of(90), ScoreLevel::High);
+        assert_eq!(ScoreLevel::of(70), ScoreLevel::High);
+        assert_eq!(ScoreLevel::of(40), ScoreLevel::Medium);
+        assert_eq!(ScoreLevel::of(39).label(), "Low");
+    }
 }
@@ -0,0 +1,368 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use crate::clock;
+use crate::killer::{self, KillError, Signal, Stage};
+use crate::paths;
+use crate::process_info::ProcessInfo;
+use crate::restart;
+
+/// What initiated a kill.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Trigger {
+    Tui,
+    Daemon,
+    /// `ccpclean --no-tui --signal`.
+    Cli,
+    /// The `kill_process` tool of `ccpclean mcp`.
+    Mcp,
+    /// `POST /processes/<pid>/kill` on `ccpclean serve`.
+    Api,
+}
+
+impl std::fmt::Display for Trigger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            Trigger::Tui => "tui",
+            Trigger::Daemon => "daemon",
+            Trigger::Cli => "cli",
+            Trigger::Mcp => "mcp",
+            Trigger::Api => "api",
+        })
+    }
+}
+
+/// One line of the append-only kill history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub time: u64,
+    pub pid: u32,
+    pub name: String,
+    pub cmd: Vec<String>,
+    pub cwd: Option<String>,
+    /// `KEY=VALUE` pairs captured just before the kill, for `ccpclean restart`: only variables
+    /// that differ from ccpclean's own environment, without secret-looking ones.
+    #[serde(default)]
+    pub env: Vec<String>,
+    pub ports: Vec<u16>,
+    pub score: u8,
+    pub signal: String,
+    /// `None` when the signal was delivered, otherwise the error message.
+    pub error: Option<String>,
+    pub trigger: Trigger,
+    pub user: Option<String>,
+}
+
+impl HistoryEntry {
+    pub fn new(
+        p: &ProcessInfo,
+        env: Vec<String>,
+        trigger: Trigger,
+        signal: Signal,
+        result: &Result<&str, KillError>,
+    ) -> Self {
+        Self {
+            time: clock::now_secs(),
+            pid: p.pid,
+            name: p.name.clone(),
+            cmd: p.cmd.clone(),
+            cwd: p.cwd.clone(),
+            env,
+            ports: p.ports.clone(),
+            score: p.score,
+            signal: signal.name().to_string(),
+            error: result.as_ref().err().map(|e| e.to_string()),
+            trigger,
+            user: std::env::var("USER").or_else(|_| std::env::var("USERNAME")).ok(),
+        }
+    }
+
+    pub fn outcome_display(&self) -> &str {
+        match self.error {
+            None => "ok",
+            Some(_) => "failed",
+        }
+    }
+}
+
+pub fn history_file() -> Option<PathBuf> {
+    paths::state_dir().map(|d| d.join("history.jsonl"))
+}
+
+pub fn append(path: &Path, entry: &HistoryEntry) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        paths::create_private_dir(dir)?;
+    }
+    // One `write_all` of the whole line on an `O_APPEND` handle, so entries from the daemon,
+    // the TUI and the servers writing at the same time do not interleave.
+    let mut line = serde_json::to_string(entry).map_err(io::Error::other)?;
+    line.push('\n');
+    let mut options = OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    {
+        // Entries carry parts of process environments. `mode` only applies when the file is
+        // created, so files left readable by older versions are tightened here too.
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        match fs::set_permissions(path, fs::Permissions::from_mode(0o600)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+    }
+    options.open(path)?.write_all(line.as_bytes())
+}
+
+/// Reads every entry with its 1-based line number, which doubles as its history ID.
+/// Malformed lines are skipped so one bad write cannot hide the rest of the log.
+pub fn read(path: &Path) -> io::Result<Vec<(usize, HistoryEntry)>> {
+    let f = match fs::File::open(path) {
+        Ok(f) => f,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut entries = Vec::new();
+    for (i, line) in BufReader::new(f).lines().enumerate() {
+        if let Ok(entry) = serde_json::from_str(&line?) {
+            entries.push((i + 1, entry));
+        }
+    }
+    Ok(entries)
+}
+
+/// Looks up a single entry by the ID shown in `ccpclean history`.
+pub fn find(path: &Path, id: usize) -> io::Result<Option<HistoryEntry>> {
+    Ok(read(path)?.into_iter().find(|(i, _)| *i == id).map(|(_, e)| e))
+}
+
+/// Captures what `p` adds to the environment, kills it and appends the attempt to the history file.
+/// Failing to write the history never blocks the kill itself.
+pub fn kill(p: &ProcessInfo, trigger: Trigger) -> Result<HistoryEntry, KillError> {
+    record(p, trigger, |pid| (Signal::Term, killer::kill(pid)))
+}
+
+/// Like `kill`, but escalates to SIGKILL after `grace` (see `killer::terminate`).
+pub fn terminate(
+    p: &ProcessInfo,
+    trigger: Trigger,
+    grace: Duration,
+    mut progress: impl FnMut(Stage),
+) -> Result<HistoryEntry, KillError> {
+    record(p, trigger, |pid| {
+        let mut sent = Signal::Term;
+        let result = killer::terminate(pid, grace, |stage| {
+            if let Stage::Sending(signal) = stage {
+                sent = signal;
+            }
+            progress(stage)
+        });
+        (sent, result)
+    })
+}
+
+/// Sends a signal that is expected to end the process (see `Signal::terminates`), waits up to
+/// `timeout` for it to exit (see `killer::signal_and_wait`) and records it like a kill.
+pub fn signal(
+    p: &ProcessInfo,
+    trigger: Trigger,
+    signal: Signal,
+    timeout: Duration,
+    progress: impl FnMut(Stage),
+) -> Result<HistoryEntry, KillError> {
+    record(p, trigger, |pid| (signal, killer::signal_and_wait(pid, signal, timeout, progress)))
+}
+
+/// `kill` returns the last signal it tried to send along with the outcome, so a failed
+/// SIGINT or SIGKILL is not logged as SIGTERM.
+fn record(
+    p: &ProcessInfo,
+    trigger: Trigger,
+    kill: impl FnOnce(u32) -> (Signal, Result<&'static str, KillError>),
+) -> Result<HistoryEntry, KillError> {
+    let env = restart::capture_restart_env(p.pid);
+    let (attempted, result) = kill(p.pid);
+    // `killer::kill` falls back to SIGKILL where there is no SIGTERM; trust what was delivered.
+    let signal = result.as_ref().ok().and_then(|name| Signal::from_name(name)).unwrap_or(attempted);
+    let entry = HistoryEntry::new(p, env, trigger, signal, &result);
+    if let Some(path) = history_file() {
+        let _ = append(&path, &entry);
+    }
+    result.map(|_| entry)
+}
+
+#[derive(Debug, Default)]
+pub struct HistoryFilter {
+    pub since: Option<u64>,
+    pub until: Option<u64>,
+    pub port: Option<u16>,
+    /// Case-insensitive substring of the process name.
+    pub name: Option<String>,
+}
+
+impl HistoryFilter {
+    pub fn matches(&self, e: &HistoryEntry) -> bool {
+        if self.since.is_some_and(|since| e.time < since) {
+            return false;
+        }
+        if self.until.is_some_and(|until| e.time >= until) {
+            return false;
+        }
+        if self.port.is_some_and(|port| !e.ports.contains(&port)) {
+            return false;
+        }
+        if let Some(ref name) = self.name {
+            if !e.name.to_lowercase().contains(&name.to_lowercase()) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Accepts `YYYY-MM-DD` or a duration relative to now such as `2d`.
+pub fn parse_time_bound(s: &str) -> Result<u64, String> {
+    if let Some(secs) = clock::parse_date(s) {
+        return Ok(secs);
+    }
+    crate::clock::parse_duration(s)
+        .map(|d| clock::now_secs().saturating_sub(d.as_secs()))
+        .map_err(|_| format!("invalid time '{}': expected YYYY-MM-DD or a duration like 2d", s))
+}
+
+pub fn print(entries: &[(usize, HistoryEntry)]) {
+    if entries.is_empty() {
+        println!("No matching kills recorded.");
+        return;
+    }
+    println!(
+        "{:<5} {:<20} {:<8} {:<12} {:<14} {:<8} {:<7} {:<7} COMMAND",
+        "ID", "TIME", "PID", "NAME", "PORTS", "SIGNAL", "RESULT", "BY"
+    );
+    println!("{}", "-".repeat(100));
+    for (id, e) in entries {
+        let ports = e.ports.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(",");
+        println!(
+            "{:<5} {:<20} {:<8} {:<12} {:<14} {:<8} {:<7} {:<7} {}",
+            id,
+            clock::format_utc(e.time),
+            e.pid,
+            e.name,
+            ports,
+            e.signal,
+            e.outcome_display(),
+            e.trigger,
+            e.cmd.join(" ")
+        );
+        if let Some(ref err) = e.error {
+            println!("      {}", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_entry(time: u64, name: &str, ports: Vec<u16>) -> HistoryEntry {
+        HistoryEntry {
+            time,
+            pid: 1,
+            name: name.to_string(),
+            cmd: vec![name.to_string()],
+            cwd: Some("/home/dev/app".to_string()),
+            env: vec!["PORT=3000".to_string()],
+            ports,
+            score: 90,
+            signal: "SIGTERM".to_string(),
+            error: None,
+            trigger: Trigger::Tui,
+            user: None,
+        }
+    }
+
+    fn temp_file(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("ccpclean-{}-{}", std::process::id(), name));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_append_and_read_assigns_ids() {
+        let path = temp_file("history.jsonl");
+        append(&path, &make_entry(100, "node", vec![3000])).unwrap();
+        append(&path, &make_entry(200, "python", vec![8000])).unwrap();
+        let entries = read(&path).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].0, 1);
+        assert_eq!(entries[1].0, 2);
+        assert_eq!(entries[1].1.name, "python");
+        assert_eq!(entries[0].1.cwd.as_deref(), Some("/home/dev/app"));
+        assert_eq!(entries[0].1.env, vec!["PORT=3000"]);
+        assert_eq!(find(&path, 2).unwrap().unwrap().name, "python");
+        assert!(find(&path, 3).unwrap().is_none());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_read_skips_malformed_lines() {
+        let path = temp_file("history-bad.jsonl");
+        append(&path, &make_entry(100, "node", vec![3000])).unwrap();
+        fs::OpenOptions::new().append(true).open(&path).unwrap().write_all(b"{oops\n").unwrap();
+        append(&path, &make_entry(300, "deno", vec![8080])).unwrap();
+        let entries = read(&path).unwrap();
+        assert_eq!(entries.iter().map(|(id, _)| *id).collect::<Vec<_>>(), vec![1, 3]);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_read_accepts_entries_without_env() {
+        let path = temp_file("history-old.jsonl");
+        fs::write(
+            &path,
+            r#"{"time":1,"pid":2,"name":"node","cmd":["node"],"cwd":null,"ports":[3000],"score":80,"signal":"SIGTERM","error":null,"trigger":"tui","user":null}"#,
+        )
+        .unwrap();
+        let entries = read(&path).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert!(entries[0].1.env.is_empty());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_read_missing_file_is_empty() {
+        assert!(read(&temp_file("missing.jsonl")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_filter() {
+        let e = make_entry(1000, "Node", vec![3000, 3001]);
+        assert!(HistoryFilter::default().matches(&e));
+        assert!(HistoryFilter { since: Some(1000), ..Default::default() }.matches(&e));
+        assert!(!HistoryFilter { since: Some(1001), ..Default::default() }.matches(&e));
+        assert!(!HistoryFilter { until: Some(1000), ..Default::default() }.matches(&e));
+        assert!(HistoryFilter { port: Some(3001), ..Default::default() }.matches(&e));
+        assert!(!HistoryFilter { port: Some(8000), ..Default::default() }.matches(&e));
+        assert!(HistoryFilter { name: Some("nod".to_string()), ..Default::default() }.matches(&e));
+        assert!(!HistoryFilter { name: Some("python".to_string()), ..Default::default() }.matches(&e));
+    }
+
+    #[test]
+    fn test_entry_records_failure() {
+        let p = ProcessInfo { pid: 9, name: "node".to_string(), ..Default::default() };
+        let e = HistoryEntry::new(&p, vec![], Trigger::Daemon, Signal::Int, &Err(KillError::PermissionDenied(9)));
+        assert_eq!(e.outcome_display(), "failed");
+        assert!(e.error.unwrap().contains("Permission denied"));
+        assert_eq!(e.signal, "SIGINT");
+    }
+
+    #[test]
+    fn test_parse_time_bound() {
+        assert_eq!(parse_time_bound("2024-02-29"), Ok(1_709_164_800));
+        let rel = parse_time_bound("1d").unwrap();
+        assert!(clock::now_secs() - rel >= 86400);
+        assert!(parse_time_bound("last week").is_err());
+    }
+}
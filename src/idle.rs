@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::process_info::ProcessInfo;
+
+/// CPU use below this share of wall time between two samples counts as "~0% CPU".
+const ACTIVE_CPU_RATIO: f64 = 0.01;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Sample {
+    pid: u32,
+    start_time_secs: u64,
+    sampled_at: u64,
+    cpu_time_ms: Option<u64>,
+    last_active: u64,
+}
+
+/// Remembers the last CPU-time sample and last activity of each process across scans, so
+/// that idleness can be measured over hours rather than within a single scan.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct IdleTracker {
+    samples: Vec<Sample>,
+}
+
+impl IdleTracker {
+    /// Loads saved samples; a missing or unreadable file starts from scratch.
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    /// Writes to a temporary file and renames it into place, so a concurrent run (daemon,
+    /// TUI, serve) never reads a half-written file; the last writer wins.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            crate::paths::create_private_dir(dir)?;
+        }
+        let text = serde_json::to_string(self).map_err(io::Error::other)?;
+        let tmp = path.with_extension(format!("json.{}.tmp", std::process::id()));
+        fs::write(&tmp, text)?;
+        fs::rename(&tmp, path).inspect_err(|_| {
+            let _ = fs::remove_file(&tmp);
+        })
+    }
+
+    /// Fills in `idle_secs` for each process and records a new sample. Processes that are no
+    /// longer present are forgotten.
+    pub fn update(&mut self, processes: &mut [ProcessInfo], now: u64) {
+        let previous: HashMap<(u32, u64), Sample> = self
+            .samples
+            .drain(..)
+            .map(|s| ((s.pid, s.start_time_secs), s))
+            .collect();
+
+        for p in processes.iter_mut() {
+            let last_active = match previous.get(&(p.pid, p.start_time_secs)) {
+                None => {
+                    p.idle_secs = None;
+                    now
+                }
+                Some(prev) => {
+                    let last_active = if is_active(prev, p, now) { now } else { prev.last_active };
+                    p.idle_secs = Some(now.saturating_sub(last_active));
+                    last_active
+                }
+            };
+            self.samples.push(Sample {
+                pid: p.pid,
+                start_time_secs: p.start_time_secs,
+                sampled_at: now,
+                cpu_time_ms: p.cpu_time_ms,
+                last_active,
+            });
+        }
+    }
+}
+
+/// Updates `processes` against the tracker saved in the state directory and saves it back.
+/// The file is only a cache, so failures to read or write it are ignored.
+pub fn track(processes: &mut [ProcessInfo]) {
+    let path = crate::paths::state_dir().map(|d| d.join("idle.json"));
+    let mut tracker = path.as_deref().map(IdleTracker::load).unwrap_or_default();
+    tracker.update(processes, crate::clock::now_secs());
+    if let Some(path) = path {
+        let _ = tracker.save(&path);
+    }
+}
+
+fn is_active(prev: &Sample, p: &ProcessInfo, now: u64) -> bool {
+    if p.connections > 0 {
+        return true;
+    }
+    match (prev.cpu_time_ms, p.cpu_time_ms) {
+        (Some(before), Some(after)) => {
+            let wall_ms = now.saturating_sub(prev.sampled_at).max(1) * 1000;
+            after.saturating_sub(before) as f64 > wall_ms as f64 * ACTIVE_CPU_RATIO
+        }
+        // Without CPU accounting only connections are considered.
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_proc(cpu_time_ms: u64, connections: usize) -> ProcessInfo {
+        ProcessInfo {
+            pid: 10,
+            name: "node".to_string(),
+            start_time_secs: 500,
+            cpu_time_ms: Some(cpu_time_ms),
+            connections,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_first_sample_is_unknown() {
+        let mut tracker = IdleTracker::default();
+        let mut procs = vec![make_proc(100, 0)];
+        tracker.update(&mut procs, 1000);
+        assert_eq!(procs[0].idle_secs, None);
+    }
+
+    #[test]
+    fn test_idle_accumulates_without_cpu_or_connections() {
+        let mut tracker = IdleTracker::default();
+        tracker.update(&mut [make_proc(100, 0)], 1000);
+
+        let mut procs = vec![make_proc(150, 0)];
+        tracker.update(&mut procs, 1600);
+        assert_eq!(procs[0].idle_secs, Some(600));
+
+        let mut procs = vec![make_proc(160, 0)];
+        tracker.update(&mut procs, 4600);
+        assert_eq!(procs[0].idle_secs, Some(3600));
+    }
+
+    #[test]
+    fn test_cpu_or_connections_reset_idle() {
+        let mut tracker = IdleTracker::default();
+        tracker.update(&mut [make_proc(100, 0)], 1000);
+
+        // 30s of CPU over 60s of wall time
+        let mut procs = vec![make_proc(30_100, 0)];
+        tracker.update(&mut procs, 1060);
+        assert_eq!(procs[0].idle_secs, Some(0));
+
+        let mut procs = vec![make_proc(30_100, 1)];
+        tracker.update(&mut procs, 5000);
+        assert_eq!(procs[0].idle_secs, Some(0));
+    }
+
+    #[test]
+    fn test_recycled_pid_starts_over() {
+        let mut tracker = IdleTracker::default();
+        tracker.update(&mut [make_proc(100, 0)], 1000);
+        let mut reused = make_proc(100, 0);
+        reused.start_time_secs = 900;
+        let mut procs = vec![reused];
+        tracker.update(&mut procs, 2000);
+        assert_eq!(procs[0].idle_secs, None);
+    }
+
+    #[test]
+    fn test_save_replaces_file() {
+        let path = std::env::temp_dir().join(format!("ccpclean-idle-test-{}.json", std::process::id()));
+        let mut tracker = IdleTracker::default();
+        tracker.update(&mut [make_proc(100, 0)], 1000);
+        tracker.save(&path).unwrap();
+        tracker.save(&path).unwrap();
+        assert_eq!(IdleTracker::load(&path).samples.len(), 1);
+        assert!(!path.with_extension(format!("json.{}.tmp", std::process::id())).exists());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_roundtrip_json() {
+        let mut tracker = IdleTracker::default();
+        tracker.update(&mut [make_proc(100, 0)], 1000);
+        let text = serde_json::to_string(&tracker).unwrap();
+        let mut restored: IdleTracker = serde_json::from_str(&text).unwrap();
+        let mut procs = vec![make_proc(100, 0)];
+        restored.update(&mut procs, 1300);
+        assert_eq!(procs[0].idle_secs, Some(300));
+    }
+}
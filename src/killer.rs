@@ -1,9 +1,89 @@
 use std::io;
+use std::thread;
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, ProcessStatus, ProcessesToUpdate, System};
+
+/// How often `terminate` checks whether the process has exited.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Signals `ccpclean` can send.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    Term,
+    Kill,
+    Int,
+    Hup,
+    Stop,
+    Cont,
+}
+
+impl Signal {
+    /// In the order of the TUI signal menu.
+    pub const ALL: [Signal; 6] = [Signal::Term, Signal::Kill, Signal::Int, Signal::Hup, Signal::Stop, Signal::Cont];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Signal::Term => "SIGTERM",
+            Signal::Kill => "SIGKILL",
+            Signal::Int => "SIGINT",
+            Signal::Hup => "SIGHUP",
+            Signal::Stop => "SIGSTOP",
+            Signal::Cont => "SIGCONT",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Signal::Term => "terminate, letting the process clean up",
+            Signal::Kill => "kill immediately",
+            Signal::Int => "interrupt, like Ctrl-C",
+            Signal::Hup => "hang up; many servers reload their config",
+            Signal::Stop => "pause until SIGCONT",
+            Signal::Cont => "resume a stopped process",
+        }
+    }
+
+    /// Parses `term`, `TERM` or `SIGTERM` (case-insensitive).
+    pub fn from_name(s: &str) -> Option<Signal> {
+        let upper = s.trim().to_ascii_uppercase();
+        let name = upper.strip_prefix("SIG").unwrap_or(&upper);
+        Signal::ALL.into_iter().find(|sig| &sig.name()[3..] == name)
+    }
+
+    /// Whether the process is expected to exit: such signals are recorded in the kill history
+    /// and can be undone.
+    pub fn terminates(self) -> bool {
+        matches!(self, Signal::Term | Signal::Kill | Signal::Int)
+    }
+
+    fn to_sysinfo(self) -> sysinfo::Signal {
+        match self {
+            Signal::Term => sysinfo::Signal::Term,
+            Signal::Kill => sysinfo::Signal::Kill,
+            Signal::Int => sysinfo::Signal::Interrupt,
+            Signal::Hup => sysinfo::Signal::Hangup,
+            Signal::Stop => sysinfo::Signal::Stop,
+            Signal::Cont => sysinfo::Signal::Continue,
+        }
+    }
+}
+
+/// Steps of `terminate`, reported as they happen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stage {
+    Sending(Signal),
+    /// SIGTERM delivered; waiting this long so far for the process to exit.
+    Waiting(Duration),
+}
 
 #[derive(Debug)]
 pub enum KillError {
     PermissionDenied(u32),
     ProcessNotFound(u32),
+    /// The platform cannot deliver this signal.
+    Unsupported(u32, Signal),
+    /// The signal was delivered but the process had not exited when the wait ran out.
+    StillRunning(u32, Signal),
     Other(u32, io::Error),
 }
 
@@ -16,33 +96,102 @@ impl std::fmt::Display for KillError {
             KillError::ProcessNotFound(pid) => {
                 write!(f, "Process {} not found (already exited?)", pid)
             }
+            KillError::Unsupported(pid, signal) => {
+                write!(f, "Cannot send {} to PID {}: not supported on this platform", signal.name(), pid)
+            }
+            KillError::StillRunning(pid, signal) => {
+                write!(f, "PID {} is still running after {}", pid, signal.name())
+            }
             KillError::Other(pid, e) => write!(f, "Failed to kill PID {}: {}", pid, e),
         }
     }
 }
 
-pub fn kill(pid: u32) -> Result<(), KillError> {
-    use sysinfo::{Pid, Signal, System};
+/// Sends `signal` to `pid`. Returns the name of the signal that was delivered.
+pub fn send_signal(pid: u32, signal: Signal) -> Result<&'static str, KillError> {
+    let sysinfo_pid = Pid::from_u32(pid);
     let mut sys = System::new();
-    sys.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+    sys.refresh_processes(ProcessesToUpdate::Some(&[sysinfo_pid]), true);
 
-    let sysinfo_pid = Pid::from_u32(pid);
-    match sys.process(sysinfo_pid) {
-        None => Err(KillError::ProcessNotFound(pid)),
-        Some(process) => {
-            match process.kill_with(Signal::Term) {
-                Some(true) => Ok(()),
-                Some(false) => Err(KillError::PermissionDenied(pid)),
-                None => {
-                    // Signal not supported on this platform, use SIGKILL
-                    if process.kill() {
-                        Ok(())
-                    } else {
-                        Err(KillError::PermissionDenied(pid))
-                    }
-                }
-            }
+    let Some(process) = sys.process(sysinfo_pid) else {
+        return Err(KillError::ProcessNotFound(pid));
+    };
+    let delivered = if signal == Signal::Kill {
+        Some(process.kill())
+    } else {
+        process.kill_with(signal.to_sysinfo())
+    };
+    match delivered {
+        Some(true) => Ok(signal.name()),
+        Some(false) => Err(KillError::PermissionDenied(pid)),
+        None => Err(KillError::Unsupported(pid, signal)),
+    }
+}
+
+/// Sends SIGTERM (or force-kills where the platform has no SIGTERM). Returns the name of the
+/// signal that was delivered.
+pub fn kill(pid: u32) -> Result<&'static str, KillError> {
+    match send_signal(pid, Signal::Term) {
+        Err(KillError::Unsupported(..)) => send_signal(pid, Signal::Kill),
+        result => result,
+    }
+}
+
+/// Whether `pid` is still alive; zombies waiting to be reaped count as exited.
+fn is_running(pid: u32) -> bool {
+    let pid = Pid::from_u32(pid);
+    let mut sys = System::new();
+    sys.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+    sys.process(pid).is_some_and(|p| p.status() != ProcessStatus::Zombie)
+}
+
+/// Sends a signal expected to end the process (see `Signal::terminates`) and waits up to
+/// `timeout` for it to exit, reporting the wait to `progress`. Unlike `terminate`, it never
+/// escalates: a server that ignores SIGINT is reported as `StillRunning`.
+pub fn signal_and_wait(
+    pid: u32,
+    signal: Signal,
+    timeout: Duration,
+    mut progress: impl FnMut(Stage),
+) -> Result<&'static str, KillError> {
+    progress(Stage::Sending(signal));
+    let name = send_signal(pid, signal)?;
+    let start = Instant::now();
+    while is_running(pid) {
+        if start.elapsed() >= timeout {
+            return Err(KillError::StillRunning(pid, signal));
+        }
+        progress(Stage::Waiting(start.elapsed()));
+        thread::sleep(POLL_INTERVAL);
+    }
+    Ok(name)
+}
+
+/// Sends SIGTERM, waits up to `grace` for the process to exit and escalates to SIGKILL if it
+/// has not, reporting each step to `progress`. Returns the last signal delivered.
+pub fn terminate(pid: u32, grace: Duration, mut progress: impl FnMut(Stage)) -> Result<&'static str, KillError> {
+    progress(Stage::Sending(Signal::Term));
+    let signal = kill(pid)?;
+    if signal == Signal::Kill.name() {
+        return Ok(signal);
+    }
+    let start = Instant::now();
+    while start.elapsed() < grace {
+        if !is_running(pid) {
+            return Ok(signal);
         }
+        progress(Stage::Waiting(start.elapsed()));
+        thread::sleep(POLL_INTERVAL);
+    }
+    if !is_running(pid) {
+        return Ok(signal);
+    }
+
+    progress(Stage::Sending(Signal::Kill));
+    match send_signal(pid, Signal::Kill) {
+        // Exited between the last check and now.
+        Err(KillError::ProcessNotFound(_)) => Ok(signal),
+        result => result,
     }
 }
 
@@ -56,6 +205,81 @@ mod tests {
         assert!(matches!(result, Err(KillError::ProcessNotFound(_))));
     }
 
+    #[cfg(unix)]
+    fn spawn(script: &str) -> std::process::Child {
+        let child = std::process::Command::new("sh").arg("-c").arg(script).spawn().unwrap();
+        // Give the shell time to install its traps.
+        thread::sleep(Duration::from_millis(200));
+        child
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_terminate_stops_at_sigterm() {
+        let mut child = spawn("exec sleep 30");
+        let mut stages = Vec::new();
+        let result = terminate(child.id(), Duration::from_secs(5), |s| stages.push(s));
+        assert_eq!(result.unwrap(), "SIGTERM");
+        assert_eq!(stages.first(), Some(&Stage::Sending(Signal::Term)));
+        assert!(!stages.contains(&Stage::Sending(Signal::Kill)));
+        child.wait().unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_terminate_escalates_to_sigkill() {
+        let mut child = spawn("trap '' TERM; exec sleep 30");
+        let mut stages = Vec::new();
+        let result = terminate(child.id(), Duration::from_millis(300), |s| stages.push(s));
+        assert_eq!(result.unwrap(), "SIGKILL");
+        assert!(stages.iter().any(|s| matches!(s, Stage::Waiting(_))));
+        assert_eq!(stages.last(), Some(&Stage::Sending(Signal::Kill)));
+        child.wait().unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_signal_and_wait_verifies_exit() {
+        let mut child = spawn("exec sleep 30");
+        assert_eq!(signal_and_wait(child.id(), Signal::Int, Duration::from_secs(5), |_| {}).unwrap(), "SIGINT");
+        child.wait().unwrap();
+
+        let mut child = spawn("trap '' INT; exec sleep 30");
+        let result = signal_and_wait(child.id(), Signal::Int, Duration::from_millis(300), |_| {});
+        assert!(matches!(result, Err(KillError::StillRunning(_, Signal::Int))));
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_send_stop_and_continue() {
+        let mut child = spawn("exec sleep 30");
+        let status = |pid| {
+            let pid = Pid::from_u32(pid);
+            let mut sys = System::new();
+            sys.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+            sys.process(pid).map(|p| p.status())
+        };
+        assert_eq!(send_signal(child.id(), Signal::Stop).unwrap(), "SIGSTOP");
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(status(child.id()), Some(ProcessStatus::Stop));
+        assert_eq!(send_signal(child.id(), Signal::Cont).unwrap(), "SIGCONT");
+        thread::sleep(Duration::from_millis(100));
+        assert_ne!(status(child.id()), Some(ProcessStatus::Stop));
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn test_signal_from_name() {
+        assert_eq!(Signal::from_name("stop"), Some(Signal::Stop));
+        assert_eq!(Signal::from_name("SIGHUP"), Some(Signal::Hup));
+        assert_eq!(Signal::from_name("Int"), Some(Signal::Int));
+        assert_eq!(Signal::from_name("usr1"), None);
+        assert_eq!(Signal::from_name("SIG"), None);
+    }
+
     #[test]
     fn test_kill_error_display() {
         let e = KillError::PermissionDenied(1234);
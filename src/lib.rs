@@ -0,0 +1,82 @@
+//! Find and clean up orphaned local web servers.
+//!
+//! The `ccpclean` binary is a front end to this library: [`scan`] lists processes with their
+//! listening ports, [`compute_score`] rates how likely each one is a forgotten dev server,
+//! [`apply_filter`] keeps the relevant ones and [`kill`] (or [`terminate`]) stops them.
+//!
+//! ```no_run
+//! use ccpclean::{apply_filter, compute_score, kill, scan, FilterMode};
+//!
+//! let mut processes = scan();
+//! for p in &mut processes {
+//!     p.score = compute_score(p);
+//! }
+//! for p in apply_filter(processes, FilterMode::Strict) {
+//!     if p.score >= 70 && p.ports.contains(&3000) {
+//!         kill(p.pid).expect("kill");
+//!     }
+//! }
+//! ```
+//!
+//! The modules below make up the library; the types [`ProcessInfo`] holds come from
+//! [`container`], [`multiplexer`], [`supervisor`] and [`systemd`]. Everything else is the
+//! `ccpclean` binary's and not a stable API.
+//!
+//! Cargo features:
+//! - `tui` (default): the terminal UI and the `ccpclean` binary, with the config file, kill
+//!   history, restarts, daemon and MCP / HTTP servers it uses. Implies `containers`.
+//! - `containers`: names the Docker / Podman container behind a port through the Engine API.
+//! - `serde`: `Serialize` / `Deserialize` for [`ProcessInfo`] and the types it holds.
+
+pub mod container;
+pub mod filter;
+pub mod killer;
+pub mod multiplexer;
+pub mod process_info;
+pub mod scanner;
+pub mod supervisor;
+pub mod systemd;
+
+mod project;
+
+// Used by the `ccpclean` binary.
+#[doc(hidden)]
+pub mod clock;
+#[doc(hidden)]
+pub mod watch;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod config;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod daemon;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod history;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod idle;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod mcp;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod restart;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod serve;
+#[cfg(feature = "tui")]
+#[doc(hidden)]
+pub mod tui;
+
+#[cfg(feature = "tui")]
+mod inspect;
+#[cfg(feature = "tui")]
+mod paths;
+#[cfg(feature = "tui")]
+mod report;
+
+pub use filter::{apply_filter, compute_score, FilterMode};
+pub use killer::{kill, send_signal, terminate, KillError, Signal};
+pub use process_info::{Listener, ProcessInfo};
+pub use scanner::scan;
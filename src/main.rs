@@ -1,26 +1,23 @@
 mod cli;
-mod filter;
-mod killer;
-mod process_info;
-mod scanner;
-mod tui;
 
 use clap::Parser;
-use cli::Cli;
-use filter::{apply_filter, compute_score, FilterMode};
-use tui::{AppState, runner};
+use cli::{Cli, Command};
+use ccpclean::config::Config;
+use ccpclean::tui::{AppState, runner};
+use ccpclean::{apply_filter, compute_score, daemon, history, idle, killer, mcp, multiplexer, restart, scanner, serve, watch};
+use ccpclean::{FilterMode, ProcessInfo, Signal};
 
-fn main() {
-    let cli = Cli::parse();
-
-    let mode = if cli.all {
-        FilterMode::Loose
-    } else {
-        FilterMode::Strict
-    };
+/// How long `--no-tui --signal` waits for a process to exit after a signal that should end it.
+const EXIT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
 
+/// Scans, scores, applies the `--port` and `--idle` filters and sorts by score descending.
+fn collect(cli: &Cli) -> Vec<ProcessInfo> {
     // Scan processes
     let mut processes = scanner::scan();
+    serve::exclude_running(&mut processes);
+
+    // Measure idleness against previous scans
+    idle::track(&mut processes);
 
     // Compute scores
     for p in &mut processes {
@@ -32,25 +29,188 @@ fn main() {
         processes.retain(|p| p.ports.contains(&port));
     }
 
+    // Apply idle filter if specified
+    if let Some(min) = cli.idle {
+        processes.retain(|p| p.idle().is_some_and(|idle| idle >= min));
+    }
+
     // Sort by score descending
-    processes.sort_by(|a, b| b.score.cmp(&a.score));
+    processes.sort_by_key(|p| std::cmp::Reverse(p.score));
+
+    processes
+}
+
+fn load_config(cli: &Cli) -> Config {
+    match Config::load(cli.config.as_deref()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn show_history(cli: &Cli, since: Option<&str>, until: Option<&str>, name: Option<String>) {
+    let bound = |s: Option<&str>| match s.map(history::parse_time_bound).transpose() {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    let filter = history::HistoryFilter {
+        since: bound(since),
+        until: bound(until),
+        port: cli.port,
+        name,
+    };
+    let Some(path) = history::history_file() else {
+        eprintln!("Cannot locate the state directory (set HOME or XDG_STATE_HOME).");
+        std::process::exit(1);
+    };
+    match history::read(&path) {
+        Ok(entries) => {
+            let entries: Vec<_> = entries.into_iter().filter(|(_, e)| filter.matches(e)).collect();
+            history::print(&entries);
+        }
+        Err(e) => {
+            eprintln!("Failed to read {}: {}", path.display(), e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn restart_entry(id: usize) {
+    let Some(path) = history::history_file() else {
+        eprintln!("Cannot locate the state directory (set HOME or XDG_STATE_HOME).");
+        std::process::exit(1);
+    };
+    let entry = match history::find(&path, id) {
+        Ok(Some(entry)) => entry,
+        Ok(None) => {
+            eprintln!("No history entry with ID {} (see `ccpclean history`).", id);
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("Failed to read {}: {}", path.display(), e);
+            std::process::exit(1);
+        }
+    };
+    match restart::relaunch(&entry) {
+        Ok((pid, log)) => {
+            println!("Restarted {} as PID {}: {}", entry.name, pid, entry.cmd.join(" "));
+            println!("Output is logged to {}", log.display());
+        }
+        Err(e) => {
+            eprintln!("Failed to restart {}: {}", entry.name, e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// `--no-tui --signal`: sends `signal` to every listed process, exiting with status 1 if any
+/// could not be signalled.
+fn signal_all(processes: &[ProcessInfo], signal: Signal) {
+    let mut failed = false;
+    for p in processes {
+        let result = if signal.terminates() {
+            history::signal(p, history::Trigger::Cli, signal, EXIT_TIMEOUT, |_| {}).map(|_| ())
+        } else {
+            killer::send_signal(p.pid, signal).map(|_| ())
+        };
+        match result {
+            Ok(()) => println!("Sent {} to PID {} ({})", signal.name(), p.pid, p.name),
+            Err(e) => {
+                eprintln!("{}", e);
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    let mode = if cli.all {
+        FilterMode::Loose
+    } else {
+        FilterMode::Strict
+    };
+
+    match cli.command {
+        Some(Command::Watch { interval }) => {
+            watch::run(interval, || apply_filter(collect(&cli), mode));
+            return;
+        }
+        Some(Command::Daemon { interval, dry_run, ref log_file }) => {
+            let mut config = load_config(&cli);
+            if let Some(interval) = interval {
+                config.daemon.interval = interval;
+            }
+            config.daemon.dry_run |= dry_run;
+            if log_file.is_some() {
+                config.daemon.log_file = log_file.clone();
+            }
+            if let Err(e) = daemon::run(&config.daemon, &config.protect, || apply_filter(collect(&cli), mode)) {
+                eprintln!("Daemon error: {}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Command::History { ref since, ref until, ref name }) => {
+            show_history(&cli, since.as_deref(), until.as_deref(), name.clone());
+            return;
+        }
+        Some(Command::Restart { id }) => {
+            restart_entry(id);
+            return;
+        }
+        Some(Command::Mcp { dry_run }) => {
+            let config = load_config(&cli);
+            if let Err(e) = mcp::run(&config.protect, dry_run, || collect(&cli)) {
+                eprintln!("MCP server error: {}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Command::Serve { listen, ref socket, dry_run }) => {
+            let config = load_config(&cli);
+            let listen = match socket {
+                Some(path) => serve::Listen::Unix(path.clone()),
+                None => serve::Listen::Tcp(listen),
+            };
+            if let Err(e) = serve::run(&listen, &config.protect, dry_run, || collect(&cli)) {
+                eprintln!("Server error: {}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        None => {}
+    }
+
+    let mut processes = collect(&cli);
 
     if cli.no_tui {
         let filtered = apply_filter(processes, mode);
-        if filtered.is_empty() {
+        if let (Some(signal), false) = (cli.signal, filtered.is_empty()) {
+            signal_all(&filtered, signal);
+        } else if filtered.is_empty() {
             println!("No matching processes found.");
             println!();
             println!("Tip: try `ccpclean --all --no-tui` to show all processes listening on local ports,");
             println!("     or `ccpclean --port <PORT> --no-tui` to filter by a specific port.");
         } else {
-            println!("{:<8} {:<12} {:<18} {:<7} {}", "PID", "NAME", "PORTS", "SCORE", "COMMAND");
+            println!("{:<8} {:<12} {:<18} {:<7} COMMAND", "PID", "NAME", "PORTS", "SCORE");
             println!("{}", "-".repeat(70));
             for p in &filtered {
                 let cmd = p.cmd.get(1).map(|s| s.as_str()).unwrap_or("");
                 println!(
                     "{:<8} {:<12} {:<18} {:<7} {}",
                     p.pid,
-                    p.name,
+                    p.name_display(),
                     p.ports_display(),
                     p.score,
                     cmd
@@ -61,7 +221,26 @@ fn main() {
     }
 
     // Always open TUI — user can switch filter mode with F
+    let config = load_config(&cli);
+    multiplexer::annotate(&mut processes);
     let mut state = AppState::new(processes);
+    state.keymap = match config.keymap() {
+        Ok(keymap) => keymap,
+        Err(e) => {
+            eprintln!("Invalid config: {}", e);
+            std::process::exit(1);
+        }
+    };
+    state.theme = match config.theme() {
+        Ok(theme) => theme,
+        Err(e) => {
+            eprintln!("Invalid config: {}", e);
+            std::process::exit(1);
+        }
+    };
+    state.protect = config.protect;
+    state.print_yanked = config.clipboard.print_on_exit;
+    state.signal = cli.signal;
     state.filter_mode = mode;
     state.refilter();
 
@@ -0,0 +1,299 @@
+use std::io::{self, BufRead, Write};
+use serde_json::{json, Value};
+use crate::config::Protect;
+use crate::filter::{apply_filter, FilterMode};
+use crate::history::Trigger;
+use crate::multiplexer;
+use crate::process_info::ProcessInfo;
+use crate::report::{self, summary};
+
+/// Used when the client does not say which protocol revision it speaks.
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+// JSON-RPC 2.0 error codes.
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+fn tools() -> Value {
+    json!([
+        {
+            "name": "list_dev_servers",
+            "description": "List processes listening on local ports, most likely forgotten dev servers first. \
+                By default only dev runtimes (node, python, deno, bun...) are listed.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "all": { "type": "boolean", "description": "Include every listening process, not only dev runtimes" },
+                    "min_score": { "type": "integer", "minimum": 0, "maximum": 100, "description": "Only processes scoring at least this" }
+                }
+            }
+        },
+        {
+            "name": "who_owns_port",
+            "description": "Show the process listening on a local TCP port, and the container, systemd unit, \
+                supervisor or terminal pane behind it.",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "port": { "type": "integer", "minimum": 1, "maximum": 65535 } },
+                "required": ["port"]
+            }
+        },
+        {
+            "name": "explain_process",
+            "description": "Explain why a listening process looks (or does not look) like a forgotten dev server: \
+                score breakdown, parent, uptime, idleness and whether it is protected.",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "pid": { "type": "integer", "minimum": 1 } },
+                "required": ["pid"]
+            }
+        },
+        {
+            "name": "kill_process",
+            "description": "Send SIGTERM to a process listening on a local port. Processes in the user's protect \
+                list are refused. The kill is recorded in `ccpclean history` and can be undone with `ccpclean restart`.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "pid": { "type": "integer", "minimum": 1 },
+                    "dry_run": { "type": "boolean", "description": "Report what would be killed without killing it" }
+                },
+                "required": ["pid"]
+            }
+        }
+    ])
+}
+
+/// A tool failure, reported to the model as an `isError` result.
+type ToolResult = Result<Value, String>;
+
+/// Answers MCP requests about the processes `collect` scans.
+pub struct Server<'a, F> {
+    protect: &'a Protect,
+    /// Never kill, whatever `kill_process` asks.
+    dry_run: bool,
+    collect: F,
+}
+
+impl<'a, F> Server<'a, F>
+where
+    F: FnMut() -> Vec<ProcessInfo>,
+{
+    /// `collect` returns every scored process, listening or not; the tools filter it.
+    pub fn new(protect: &'a Protect, dry_run: bool, collect: F) -> Self {
+        Self { protect, dry_run, collect }
+    }
+
+    fn list_dev_servers(&mut self, args: &Value) -> ToolResult {
+        let mode = if args["all"].as_bool().unwrap_or(false) {
+            FilterMode::Loose
+        } else {
+            FilterMode::Strict
+        };
+        let min_score = args["min_score"].as_u64().unwrap_or(0);
+        let mut processes = apply_filter((self.collect)(), mode);
+        processes.retain(|p| u64::from(p.score) >= min_score);
+        processes.sort_by_key(|p| std::cmp::Reverse(p.score));
+        Ok(Value::Array(processes.iter().map(summary).collect()))
+    }
+
+    fn who_owns_port(&mut self, args: &Value) -> ToolResult {
+        let port = u16_arg(args, "port")?;
+        let mut owners = apply_filter((self.collect)(), FilterMode::Loose);
+        owners.retain(|p| p.ports.contains(&port));
+        multiplexer::annotate(&mut owners);
+        let owners: Vec<Value> = owners.iter().map(summary).collect();
+        if owners.is_empty() {
+            Ok(json!(format!("Nothing is listening on port {}.", port)))
+        } else {
+            Ok(Value::Array(owners))
+        }
+    }
+
+    fn explain_process(&mut self, args: &Value) -> ToolResult {
+        let p = report::listening((self.collect)(), u32_arg(args, "pid")?).map_err(|e| e.to_string())?;
+        Ok(report::explain(&p, self.protect))
+    }
+
+    fn kill_process(&mut self, args: &Value) -> ToolResult {
+        let p = report::listening((self.collect)(), u32_arg(args, "pid")?).map_err(|e| e.to_string())?;
+        let dry_run = self.dry_run || args["dry_run"].as_bool().unwrap_or(false);
+        report::kill(&p, self.protect, dry_run, Trigger::Mcp)
+            .map(Value::String)
+            .map_err(|e| e.to_string())
+    }
+
+    fn call_tool(&mut self, params: &Value) -> Result<Value, (i64, String)> {
+        let args = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+        let result = match params["name"].as_str() {
+            Some("list_dev_servers") => self.list_dev_servers(&args),
+            Some("who_owns_port") => self.who_owns_port(&args),
+            Some("explain_process") => self.explain_process(&args),
+            Some("kill_process") => self.kill_process(&args),
+            Some(name) => return Err((INVALID_PARAMS, format!("Unknown tool: {}", name))),
+            None => return Err((INVALID_PARAMS, "Missing tool name".to_string())),
+        };
+        let (text, is_error) = match result {
+            Ok(Value::String(text)) => (text, false),
+            Ok(value) => (serde_json::to_string_pretty(&value).unwrap_or_default(), false),
+            Err(message) => (message, true),
+        };
+        Ok(json!({ "content": [{ "type": "text", "text": text }], "isError": is_error }))
+    }
+
+    /// Answers one JSON-RPC message; notifications get no answer.
+    pub fn handle(&mut self, msg: &Value) -> Option<Value> {
+        let id = msg.get("id").cloned()?;
+        let Some(method) = msg["method"].as_str() else {
+            return Some(error(id, INVALID_REQUEST, "Missing method"));
+        };
+        let params = &msg["params"];
+        let result = match method {
+            "initialize" => Ok(json!({
+                "protocolVersion": params["protocolVersion"].as_str().unwrap_or(PROTOCOL_VERSION),
+                "capabilities": { "tools": {} },
+                "serverInfo": { "name": "ccpclean", "version": env!("CARGO_PKG_VERSION") },
+            })),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": tools() })),
+            "tools/call" => self.call_tool(params),
+            _ => Err((METHOD_NOT_FOUND, format!("Method not found: {}", method))),
+        };
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => error(id, code, &message),
+        })
+    }
+}
+
+fn error(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+fn u32_arg(args: &Value, name: &str) -> Result<u32, String> {
+    args[name]
+        .as_u64()
+        .and_then(|n| u32::try_from(n).ok())
+        .ok_or_else(|| format!("`{}` must be a positive integer", name))
+}
+
+fn u16_arg(args: &Value, name: &str) -> Result<u16, String> {
+    args[name]
+        .as_u64()
+        .and_then(|n| u16::try_from(n).ok())
+        .ok_or_else(|| format!("`{}` must be a port number", name))
+}
+
+/// Serves MCP over stdio: one JSON-RPC message per line in, one per line out, until stdin
+/// closes. Stdout carries nothing but protocol messages.
+pub fn run<F>(protect: &Protect, dry_run: bool, collect: F) -> io::Result<()>
+where
+    F: FnMut() -> Vec<ProcessInfo>,
+{
+    let mut server = Server::new(protect, dry_run, collect);
+    let stdin = io::stdin();
+    let mut stdout = io::stdout().lock();
+    for line in stdin.lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(msg) => server.handle(&msg),
+            Err(e) => Some(error(Value::Null, PARSE_ERROR, &e.to_string())),
+        };
+        if let Some(response) = response {
+            writeln!(stdout, "{}", response)?;
+            stdout.flush()?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_proc(pid: u32, name: &str, port: u16, score: u8) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            name: name.to_string(),
+            cmd: vec![name.to_string(), "server.js".to_string()],
+            ports: vec![port],
+            start_time_secs: crate::clock::now_secs() - 3600,
+            parent_pid: Some(1),
+            parent_name: Some("init".to_string()),
+            is_dev_runtime: name == "node",
+            score,
+            ..Default::default()
+        }
+    }
+
+    fn processes() -> Vec<ProcessInfo> {
+        vec![make_proc(4242, "node", 3000, 90), make_proc(4343, "nginx", 8080, 20)]
+    }
+
+    fn call(server: &mut Server<'_, fn() -> Vec<ProcessInfo>>, tool: &str, args: Value) -> (String, bool) {
+        let msg = json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/call", "params": { "name": tool, "arguments": args } });
+        let result = &server.handle(&msg).unwrap()["result"];
+        (result["content"][0]["text"].as_str().unwrap().to_string(), result["isError"].as_bool().unwrap())
+    }
+
+    #[test]
+    fn test_initialize_and_list_tools() {
+        let protect = Protect::default();
+        let mut server = Server::new(&protect, false, processes as fn() -> Vec<ProcessInfo>);
+        let init = json!({ "jsonrpc": "2.0", "id": 0, "method": "initialize", "params": { "protocolVersion": "2025-03-26" } });
+        let response = server.handle(&init).unwrap();
+        assert_eq!(response["result"]["protocolVersion"], "2025-03-26");
+        assert_eq!(response["result"]["serverInfo"]["name"], "ccpclean");
+        assert_eq!(server.handle(&json!({ "jsonrpc": "2.0", "method": "notifications/initialized" })), None);
+
+        let list = server.handle(&json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" })).unwrap();
+        let names: Vec<&str> = list["result"]["tools"].as_array().unwrap().iter().map(|t| t["name"].as_str().unwrap()).collect();
+        assert_eq!(names, ["list_dev_servers", "who_owns_port", "explain_process", "kill_process"]);
+
+        let unknown = server.handle(&json!({ "jsonrpc": "2.0", "id": 2, "method": "resources/list" })).unwrap();
+        assert_eq!(unknown["error"]["code"], METHOD_NOT_FOUND);
+    }
+
+    #[test]
+    fn test_list_and_explain() {
+        let protect = Protect::default();
+        let mut server = Server::new(&protect, false, processes as fn() -> Vec<ProcessInfo>);
+        let (text, _) = call(&mut server, "list_dev_servers", json!({}));
+        let listed: Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(listed.as_array().unwrap().len(), 1);
+        assert_eq!(listed[0]["pid"], 4242);
+
+        let (text, _) = call(&mut server, "who_owns_port", json!({ "port": 8080 }));
+        assert!(text.contains("nginx"));
+        let (text, is_error) = call(&mut server, "who_owns_port", json!({ "port": 9999 }));
+        assert_eq!((text.as_str(), is_error), ("Nothing is listening on port 9999.", false));
+
+        let (text, _) = call(&mut server, "explain_process", json!({ "pid": 4242 }));
+        let explained: Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(explained["likelihood"], "High");
+        assert!(explained["score_factors"].as_array().unwrap().iter().any(|f| f["reason"] == "dev runtime"));
+        let (_, is_error) = call(&mut server, "explain_process", json!({ "pid": 1 }));
+        assert!(is_error);
+    }
+
+    #[test]
+    fn test_kill_respects_protect_and_dry_run() {
+        let protect = Protect { ports: vec![8080], ..Default::default() };
+        let mut server = Server::new(&protect, false, processes as fn() -> Vec<ProcessInfo>);
+        let (text, is_error) = call(&mut server, "kill_process", json!({ "pid": 4343 }));
+        assert!(is_error);
+        assert!(text.contains("protected"));
+        let (text, is_error) = call(&mut server, "kill_process", json!({ "pid": 4242, "dry_run": true }));
+        assert!(!is_error);
+        assert!(text.starts_with("Dry run: would send SIGTERM to PID 4242"));
+
+        let mut server = Server::new(&protect, true, processes as fn() -> Vec<ProcessInfo>);
+        let (text, _) = call(&mut server, "kill_process", json!({ "pid": 4242 }));
+        assert!(text.starts_with("Dry run"));
+    }
+}
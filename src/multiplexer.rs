@@ -0,0 +1,271 @@
+use std::collections::HashMap;
+use std::env;
+use std::io;
+use std::process::{Command, Output};
+use crate::process_info::ProcessInfo;
+use crate::scanner;
+
+/// Fields asked of `tmux list-panes`, tab separated.
+const TMUX_FORMAT: &str = "#{pane_id}\t#{pane_tty}\t#{session_name}\t#{window_index}\t#{pane_index}";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Kind {
+    Tmux,
+    Screen,
+}
+
+/// The tmux pane or screen window a process's terminal belongs to.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pane {
+    pub kind: Kind,
+    /// tmux session name, or screen's `$STY` (`12345.work`).
+    pub session: String,
+    pub window: String,
+    /// tmux pane index; screen has none.
+    pub pane: Option<String>,
+    /// tmux pane ID (`%3`), a stable target for tmux commands.
+    pub id: Option<String>,
+    /// The pane's terminal device (`/dev/pts/5`).
+    pub tty: Option<String>,
+}
+
+impl Pane {
+    /// `tmux: work:2.1`, `screen: work:3`.
+    pub fn display(&self) -> String {
+        match self.kind {
+            Kind::Tmux => format!("tmux: {}:{}.{}", self.session, self.window, self.pane.as_deref().unwrap_or("0")),
+            Kind::Screen => {
+                let name = self.session.split_once('.').map_or(self.session.as_str(), |(_, name)| name);
+                format!("screen: {}:{}", name, self.window)
+            }
+        }
+    }
+
+    /// Whether `ccpclean` itself runs in this pane's tmux server or screen session, so it
+    /// can switch there without detaching.
+    pub fn is_local(&self) -> bool {
+        match self.kind {
+            Kind::Tmux => env::var_os("TMUX").is_some(),
+            Kind::Screen => env::var("STY").is_ok_and(|sty| sty == self.session),
+        }
+    }
+
+    /// Whether this is the pane or window `ccpclean` itself runs in.
+    pub fn is_own(&self) -> bool {
+        match self.kind {
+            Kind::Tmux => env::var("TMUX_PANE").is_ok_and(|id| self.id.as_deref() == Some(id.as_str())),
+            Kind::Screen => self.is_local() && env::var("WINDOW").is_ok_and(|w| w == self.window),
+        }
+    }
+
+    fn tmux_target(&self) -> String {
+        self.id.clone().unwrap_or_else(|| format!("{}:{}", self.session, self.window))
+    }
+
+    /// Brings the pane up in the current client: `tmux switch-client`, or `screen -X select`
+    /// for a window of the current screen session.
+    pub fn switch_to(&self) -> io::Result<()> {
+        match self.kind {
+            Kind::Tmux => run(Command::new("tmux").args(["switch-client", "-t", &self.tmux_target()])),
+            Kind::Screen => run(Command::new("screen").args(["-X", "select", &self.window])),
+        }
+    }
+
+    /// Attaches the terminal to the pane's session with the pane selected; blocks until
+    /// the user detaches.
+    pub fn attach(&self) -> io::Result<()> {
+        let status = match self.kind {
+            Kind::Tmux => Command::new("tmux")
+                .args(["attach-session", "-t", &self.session, ";", "select-window", "-t"])
+                .arg(format!("{}:{}", self.session, self.window))
+                .args([";", "select-pane", "-t", &self.tmux_target()])
+                .status()?,
+            Kind::Screen => Command::new("screen").args(["-x", &self.session, "-p", &self.window]).status()?,
+        };
+        if status.success() {
+            Ok(())
+        } else {
+            Err(io::Error::other(format!("exited with {}", status)))
+        }
+    }
+
+    /// Closes the pane (or screen window); the processes in it get SIGHUP. Refuses to close
+    /// the pane `ccpclean` runs in.
+    pub fn kill(&self) -> io::Result<()> {
+        if self.is_own() {
+            return Err(io::Error::other("ccpclean is running in it"));
+        }
+        match self.kind {
+            Kind::Tmux => run(Command::new("tmux").args(["kill-pane", "-t", &self.tmux_target()])),
+            Kind::Screen => run(Command::new("screen").args(["-S", &self.session, "-p", &self.window, "-X", "kill"])),
+        }
+    }
+}
+
+fn run(cmd: &mut Command) -> io::Result<()> {
+    let Output { status, stderr, .. } = cmd.output()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(String::from_utf8_lossy(&stderr).trim().to_string()))
+    }
+}
+
+fn parse_list_panes(output: &str) -> Vec<Pane> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let (id, tty, session, window, pane) =
+                (fields.next()?, fields.next()?, fields.next()?, fields.next()?, fields.next()?);
+            Some(Pane {
+                kind: Kind::Tmux,
+                session: session.to_string(),
+                window: window.to_string(),
+                pane: Some(pane.to_string()),
+                id: Some(id.to_string()),
+                tty: Some(tty.to_string()),
+            })
+        })
+        .collect()
+}
+
+/// Every pane of the default tmux server; empty when tmux is missing or not running.
+fn tmux_panes() -> Vec<Pane> {
+    Command::new("tmux")
+        .args(["list-panes", "-a", "-F", TMUX_FORMAT])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| parse_list_panes(&String::from_utf8_lossy(&o.stdout)))
+        .unwrap_or_default()
+}
+
+/// The `tty_nr` field of `/proc/<pid>/stat`; 0 means no controlling terminal.
+fn parse_stat_tty(stat: &str) -> Option<u64> {
+    // The command name may contain spaces and parentheses; fields resume after the last `)`.
+    let fields = &stat[stat.rfind(')')? + 1..];
+    fields.split_whitespace().nth(4)?.parse().ok().filter(|&tty| tty != 0)
+}
+
+#[cfg(target_os = "linux")]
+fn controlling_tty(pid: u32) -> Option<u64> {
+    parse_stat_tty(&std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn controlling_tty(_pid: u32) -> Option<u64> {
+    None
+}
+
+/// Device number of a terminal, in the same encoding as `tty_nr`.
+#[cfg(unix)]
+fn device(path: &str) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    std::fs::metadata(path).ok().map(|m| m.rdev())
+}
+
+#[cfg(not(unix))]
+fn device(_path: &str) -> Option<u64> {
+    None
+}
+
+/// Matches a process to a pane by controlling terminal, then by the `$TMUX_PANE` or
+/// `$STY` / `$WINDOW` it inherited, which survive the process detaching from the terminal.
+fn find_pane(panes: &[(Pane, Option<u64>)], tty: Option<u64>, env: &[String]) -> Option<Pane> {
+    if let Some((pane, _)) = tty.and_then(|tty| panes.iter().find(|(_, dev)| *dev == Some(tty))) {
+        return Some(pane.clone());
+    }
+    let var = |name: &str| env.iter().find_map(|v| v.strip_prefix(name)?.strip_prefix('='));
+    if let Some(id) = var("TMUX_PANE") {
+        if let Some((pane, _)) = panes.iter().find(|(p, _)| p.id.as_deref() == Some(id)) {
+            return Some(pane.clone());
+        }
+    }
+    match (var("STY"), var("WINDOW")) {
+        (Some(sty), Some(window)) => Some(Pane {
+            kind: Kind::Screen,
+            session: sty.to_string(),
+            window: window.to_string(),
+            pane: None,
+            id: None,
+            tty: None,
+        }),
+        _ => None,
+    }
+}
+
+/// Finds the tmux pane or screen window of every listening process. This asks tmux and reads
+/// every process's environment, so scans leave it out; the TUI and the single-process reports
+/// call it when they need panes.
+pub fn annotate(processes: &mut [ProcessInfo]) {
+    if processes.iter().all(|p| p.ports.is_empty()) {
+        return;
+    }
+    let mut devices: HashMap<String, Option<u64>> = HashMap::new();
+    let panes: Vec<(Pane, Option<u64>)> = tmux_panes()
+        .into_iter()
+        .map(|pane| {
+            let tty = pane.tty.clone().unwrap_or_default();
+            let dev = *devices.entry(tty.clone()).or_insert_with(|| device(&tty));
+            (pane, dev)
+        })
+        .collect();
+    for p in processes.iter_mut().filter(|p| !p.ports.is_empty()) {
+        p.pane = find_pane(&panes, controlling_tty(p.pid), &scanner::capture_env(p.pid));
+    }
+}
+
+/// The tmux pane or screen window of one listening process.
+pub fn pane_of(p: &ProcessInfo) -> Option<Pane> {
+    let mut one = [p.clone()];
+    annotate(&mut one);
+    one[0].pane.take()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tmux_pane(id: &str, session: &str, window: &str, pane: &str) -> Pane {
+        Pane {
+            kind: Kind::Tmux,
+            session: session.to_string(),
+            window: window.to_string(),
+            pane: Some(pane.to_string()),
+            id: Some(id.to_string()),
+            tty: Some("/dev/pts/5".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_parse_list_panes() {
+        let panes = parse_list_panes("%0\t/dev/pts/1\tmain\t0\t0\n%3\t/dev/pts/5\twork\t2\t1\nbogus\n");
+        assert_eq!(panes.len(), 2);
+        assert_eq!(panes[1], tmux_pane("%3", "work", "2", "1"));
+        assert_eq!(panes[1].display(), "tmux: work:2.1");
+    }
+
+    #[test]
+    fn test_parse_stat_tty() {
+        assert_eq!(parse_stat_tty("4242 (node) S 1 4242 4242 34821 4242 4194304"), Some(34821));
+        assert_eq!(parse_stat_tty("4242 (my (odd) name) S 1 4242 4242 34821 4242"), Some(34821));
+        assert_eq!(parse_stat_tty("4242 (node) S 1 4242 4242 0 -1"), None);
+    }
+
+    #[test]
+    fn test_find_pane() {
+        let panes = vec![(tmux_pane("%0", "main", "0", "0"), Some(34816)), (tmux_pane("%3", "work", "2", "1"), Some(34821))];
+        let found = |tty, env: &[&str]| {
+            let env: Vec<String> = env.iter().map(|s| s.to_string()).collect();
+            find_pane(&panes, tty, &env).map(|p| p.display())
+        };
+        assert_eq!(found(Some(34821), &[]).as_deref(), Some("tmux: work:2.1"));
+        // Detached from its terminal, but still carrying the pane's environment.
+        assert_eq!(found(None, &["TMUX_PANE=%0"]).as_deref(), Some("tmux: main:0.0"));
+        assert_eq!(found(None, &["STY=12345.dev", "WINDOW=3"]).as_deref(), Some("screen: dev:3"));
+        assert_eq!(found(Some(1), &["TMUX_PANE=%9"]), None);
+    }
+}
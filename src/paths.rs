@@ -0,0 +1,73 @@
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+const APP_DIR: &str = "ccpclean";
+
+fn env_dir(var: &str) -> Option<PathBuf> {
+    env::var_os(var).filter(|v| !v.is_empty()).map(PathBuf::from)
+}
+
+fn home_dir() -> Option<PathBuf> {
+    env_dir("HOME").or_else(|| env_dir("USERPROFILE"))
+}
+
+/// `$XDG_CONFIG_HOME/ccpclean`, `~/.config/ccpclean` or `%APPDATA%\ccpclean`.
+pub fn config_dir() -> Option<PathBuf> {
+    let base = if cfg!(windows) {
+        env_dir("APPDATA")
+    } else {
+        env_dir("XDG_CONFIG_HOME").or_else(|| home_dir().map(|h| h.join(".config")))
+    };
+    base.map(|b| b.join(APP_DIR))
+}
+
+/// `$XDG_STATE_HOME/ccpclean`, `~/.local/state/ccpclean` or `%LOCALAPPDATA%\ccpclean`.
+pub fn state_dir() -> Option<PathBuf> {
+    let base = if cfg!(windows) {
+        env_dir("LOCALAPPDATA")
+    } else {
+        env_dir("XDG_STATE_HOME").or_else(|| home_dir().map(|h| h.join(".local").join("state")))
+    };
+    base.map(|b| b.join(APP_DIR))
+}
+
+/// Creates a directory under the state directory (and any missing parents). On Unix it is
+/// restricted to the owner on every call, since history and logs may hold environments.
+pub fn create_private_dir(dir: &Path) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(dir, fs::Permissions::from_mode(0o700))?;
+    }
+    Ok(())
+}
+
+/// Writes `contents` to a file readable by the owner only, creating its directory with
+/// `create_private_dir`. The permissions are set on every write, not just on creation, so a
+/// file left readable by an older version is tightened too.
+pub fn write_private(path: &Path, contents: &str) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        create_private_dir(dir)?;
+    }
+    let mut options = fs::OpenOptions::new();
+    options.create(true).write(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(contents.as_bytes())
+}
+
+pub fn config_file() -> Option<PathBuf> {
+    config_dir().map(|d| d.join("config.toml"))
+}
@@ -1,17 +1,109 @@
+use std::net::IpAddr;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use crate::container::Container;
+use crate::multiplexer::Pane;
+use crate::supervisor::Supervisor;
+use crate::systemd::Unit;
 
-#[derive(Debug, Clone)]
+/// Processes that adopt orphans: init, systemd (including `systemd --user` subreapers) and launchd.
+const ORPHAN_REAPERS: &[&str] = &["init", "systemd", "launchd"];
+
+/// Accounts below this UID belong to the OS rather than a person.
+#[cfg(target_os = "macos")]
+const FIRST_REGULAR_UID: u32 = 500;
+#[cfg(not(target_os = "macos"))]
+const FIRST_REGULAR_UID: u32 = 1000;
+
+/// A listening TCP socket.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Listener {
+    pub address: IpAddr,
+    pub port: u16,
+}
+
+impl Listener {
+    pub fn protocol(&self) -> &'static str {
+        match self.address {
+            IpAddr::V4(_) => "tcp",
+            IpAddr::V6(_) => "tcp6",
+        }
+    }
+
+    /// `*` for the unspecified (all interfaces) address.
+    pub fn address_display(&self) -> String {
+        if self.address.is_unspecified() {
+            "*".to_string()
+        } else {
+            self.address.to_string()
+        }
+    }
+}
+
+/// A process as seen by one scan (see `scanner::scan`).
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ProcessInfo {
     pub pid: u32,
     pub name: String,
     pub cmd: Vec<String>,
+    pub cwd: Option<String>,
+    /// Project directory containing `cwd` (see `project::project_root`).
+    pub project_root: Option<String>,
     pub ports: Vec<u16>,
+    /// The sockets behind `ports`, with bind address.
+    pub listeners: Vec<Listener>,
     pub start_time_secs: u64,
     pub memory_kb: u64,
     pub parent_pid: Option<u32>,
     pub parent_name: Option<String>,
+    /// Owning user; not available on Windows.
+    pub uid: Option<u32>,
     pub is_dev_runtime: bool,
     pub score: u8,
+    /// Total user + system CPU time consumed so far, where the platform exposes it.
+    pub cpu_time_ms: Option<u64>,
+    /// ESTABLISHED TCP connections on any of the listening ports.
+    pub connections: usize,
+    /// Seconds since the process last had a connection or used CPU; `None` until it has been
+    /// sampled at least twice (see `idle::IdleTracker`).
+    pub idle_secs: Option<u64>,
+    /// Paused by SIGSTOP (or a terminal's Ctrl-Z) until it gets SIGCONT.
+    pub stopped: bool,
+    /// The Docker/Podman container the ports really belong to, for port proxies and
+    /// processes running inside a container (see `container::annotate`).
+    pub container: Option<Container>,
+    /// The systemd service or transient unit that would restart the process.
+    pub unit: Option<Unit>,
+    /// The watcher or process manager (nodemon, pm2...) that would respawn the process.
+    pub supervisor: Option<Supervisor>,
+    /// The tmux pane or screen window the process runs in.
+    pub pane: Option<Pane>,
+}
+
+/// Runtime options whose value is a separate argument (`-cp lib.jar`, `-r dotenv/config`).
+/// Flags such as Python's `-I` (isolated mode) take no value and must not be listed.
+const OPTIONS_WITH_VALUE: &[&str] = &[
+    "-cp", "-classpath", "--class-path", "-p", "--module-path", "-r", "--require", "--loader",
+    "--import", "-W", "-X",
+];
+
+fn duration_display(secs: u64) -> String {
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 {
+        format!("{}m", secs / 60)
+    } else {
+        format!("{}h {}m", secs / 3600, (secs % 3600) / 60)
+    }
+}
+
+pub fn memory_display(kb: u64) -> String {
+    if kb < 1024 {
+        format!("{} KB", kb)
+    } else {
+        format!("{:.1} MB", kb as f64 / 1024.0)
+    }
 }
 
 impl ProcessInfo {
@@ -25,24 +117,76 @@ impl ProcessInfo {
     }
 
     pub fn uptime_display(&self) -> String {
-        let secs = self.uptime().as_secs();
-        if secs < 60 {
-            format!("{}s ago", secs)
-        } else if secs < 3600 {
-            format!("{}m ago", secs / 60)
-        } else {
-            format!("{}h {}m ago", secs / 3600, (secs % 3600) / 60)
+        format!("{} ago", duration_display(self.uptime().as_secs()))
+    }
+
+    pub fn idle(&self) -> Option<Duration> {
+        self.idle_secs.map(Duration::from_secs)
+    }
+
+    pub fn idle_display(&self) -> String {
+        match self.idle_secs {
+            None => "unknown (needs another scan)".to_string(),
+            Some(secs) if secs < 60 && self.connections > 0 => format!("active ({} connections)", self.connections),
+            Some(secs) if secs < 60 => "active (using CPU)".to_string(),
+            Some(secs) => format!("idle for {} (no connections, ~0% CPU)", duration_display(secs)),
         }
     }
 
     pub fn memory_display(&self) -> String {
-        if self.memory_kb < 1024 {
-            format!("{} KB", self.memory_kb)
+        memory_display(self.memory_kb)
+    }
+
+    /// Runs as root or another system account.
+    pub fn is_system(&self) -> bool {
+        self.uid.is_some_and(|uid| uid < FIRST_REGULAR_UID)
+    }
+
+    /// True when the parent is gone or the process has been reparented to init/launchd.
+    pub fn is_orphaned(&self) -> bool {
+        match (self.parent_pid, &self.parent_name) {
+            (None, _) | (_, None) | (Some(1), _) => true,
+            (Some(_), Some(name)) => {
+                let lower = name.to_lowercase();
+                ORPHAN_REAPERS.iter().any(|&r| lower == r)
+            }
+        }
+    }
+
+    /// The name with a ⏸ marker when the process is stopped or ↻ when a supervisor would
+    /// respawn it, followed by the container it serves (`docker-proxy → web`).
+    pub fn name_display(&self) -> String {
+        let name = if self.stopped {
+            format!("⏸ {}", self.name)
+        } else if self.supervisor.is_some() {
+            format!("↻ {}", self.name)
         } else {
-            format!("{:.1} MB", self.memory_kb as f64 / 1024.0)
+            self.name.clone()
+        };
+        match self.container {
+            Some(ref c) => format!("{} → {}", name, c.name),
+            None => name,
         }
     }
 
+    /// Index in `cmd` of the script, module, jar or main class the runtime runs: the first
+    /// argument after `cmd[0]` that is not an option or an option's value.
+    pub fn entrypoint(&self) -> Option<usize> {
+        let mut args = self.cmd.iter().enumerate().skip(1);
+        while let Some((i, arg)) = args.next() {
+            match arg.as_str() {
+                // The module / jar itself is the entrypoint.
+                "-m" | "-jar" => return args.next().map(|(i, _)| i),
+                a if OPTIONS_WITH_VALUE.contains(&a) => {
+                    args.next();
+                }
+                a if a.starts_with('-') => {}
+                _ => return Some(i),
+            }
+        }
+        None
+    }
+
     pub fn ports_display(&self) -> String {
         self.ports
             .iter()
@@ -72,6 +216,7 @@ mod tests {
             parent_name: Some("bash".to_string()),
             is_dev_runtime: true,
             score: 90,
+            ..Default::default()
         }
     }
 
@@ -82,6 +227,19 @@ mod tests {
         assert!(display.contains("h"), "expected hours: {}", display);
     }
 
+    #[test]
+    fn test_idle_display() {
+        let mut p = make_process();
+        assert!(p.idle_display().starts_with("unknown"));
+        p.idle_secs = Some(5);
+        p.connections = 2;
+        assert_eq!(p.idle_display(), "active (2 connections)");
+        p.connections = 0;
+        assert_eq!(p.idle_display(), "active (using CPU)");
+        p.idle_secs = Some(7380);
+        assert_eq!(p.idle_display(), "idle for 2h 3m (no connections, ~0% CPU)");
+    }
+
     #[test]
     fn test_memory_display_mb() {
         let p = make_process();
@@ -94,10 +252,45 @@ mod tests {
         assert_eq!(p.ports_display(), "3000, 3001");
     }
 
+    #[test]
+    fn test_is_system() {
+        let mut p = make_process();
+        assert!(!p.is_system());
+        p.uid = Some(0);
+        assert!(p.is_system());
+        p.uid = Some(FIRST_REGULAR_UID);
+        assert!(!p.is_system());
+    }
+
+    #[test]
+    fn test_is_orphaned() {
+        let mut p = make_process();
+        assert!(!p.is_orphaned());
+        p.parent_name = Some("systemd".to_string());
+        assert!(p.is_orphaned());
+        p.parent_pid = None;
+        p.parent_name = None;
+        assert!(p.is_orphaned());
+    }
+
     #[test]
     fn test_ports_display_empty() {
         let mut p = make_process();
         p.ports = vec![];
         assert_eq!(p.ports_display(), "");
     }
+
+    #[test]
+    fn test_entrypoint() {
+        let entry = |cmd: &[&str]| {
+            let p = ProcessInfo { cmd: cmd.iter().map(|s| s.to_string()).collect(), ..Default::default() };
+            p.entrypoint()
+        };
+        assert_eq!(entry(&["node", "--inspect", "-r", "dotenv/config", "server.js", "--port", "3000"]), Some(4));
+        assert_eq!(entry(&["python3", "-u", "-m", "http.server", "8000"]), Some(3));
+        assert_eq!(entry(&["python3", "-I", "app.py"]), Some(2));
+        assert_eq!(entry(&["java", "-Xmx2g", "-cp", "lib/*", "com.example.App"]), Some(4));
+        assert_eq!(entry(&["java", "-jar", "app.jar", "--server.port=8080"]), Some(2));
+        assert_eq!(entry(&["nginx"]), None);
+    }
 }
@@ -0,0 +1,62 @@
+use std::path::{Path, PathBuf};
+
+/// Files that mark the root of a single package.
+const MANIFESTS: &[&str] = &[
+    "package.json",
+    "Cargo.toml",
+    "pyproject.toml",
+    "go.mod",
+    "Gemfile",
+    "pom.xml",
+    "build.gradle",
+    "composer.json",
+    "deno.json",
+];
+
+/// The project a working directory belongs to: the enclosing repository (the nearest ancestor
+/// containing `.git`), so services of one monorepo share a root; otherwise the nearest
+/// ancestor with a package manifest; otherwise `dir` itself.
+pub fn project_root(dir: &Path) -> PathBuf {
+    if let Some(repo) = dir.ancestors().find(|d| d.join(".git").exists()) {
+        return repo.to_path_buf();
+    }
+    dir.ancestors()
+        .find(|d| MANIFESTS.iter().any(|m| d.join(m).is_file()))
+        .unwrap_or(dir)
+        .to_path_buf()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ccpclean-project-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_project_root_prefers_repository() {
+        let root = scratch("repo");
+        fs::create_dir_all(root.join(".git")).unwrap();
+        let service = root.join("services").join("api");
+        fs::create_dir_all(service.join("src")).unwrap();
+        fs::write(service.join("package.json"), "{}").unwrap();
+        assert_eq!(project_root(&service.join("src")), root);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_project_root_manifest_and_fallback() {
+        let root = scratch("manifest");
+        let app = root.join("app");
+        fs::create_dir_all(app.join("bin")).unwrap();
+        fs::write(app.join("Cargo.toml"), "").unwrap();
+        assert_eq!(project_root(&app.join("bin")), app);
+        assert_eq!(project_root(&root), root);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,102 @@
+use serde_json::{json, Value};
+use crate::config::Protect;
+use crate::filter::{apply_filter, score_factors, FilterMode, ScoreLevel};
+use crate::history::{self, Trigger};
+use crate::killer::KillError;
+use crate::multiplexer;
+use crate::process_info::ProcessInfo;
+
+/// Why `kill` did not kill.
+#[derive(Debug)]
+pub enum ActionError {
+    NotListening(u32),
+    /// Matched by the `[protect]` section of the config.
+    Protected(u32, String),
+    Kill(KillError),
+}
+
+impl std::fmt::Display for ActionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ActionError::NotListening(pid) => write!(f, "PID {} is not listening on any local port", pid),
+            ActionError::Protected(pid, name) => write!(
+                f,
+                "PID {} ({}) is protected by the [protect] section of the ccpclean config; ask the user to stop it",
+                pid, name
+            ),
+            ActionError::Kill(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// What manages a process and may restart or outlive it after a kill.
+pub fn owner(p: &ProcessInfo) -> Option<String> {
+    if let Some(ref c) = p.container {
+        Some(format!("container {}", c.display()))
+    } else if let Some(ref u) = p.unit {
+        Some(format!("systemd unit {}", u.display()))
+    } else {
+        p.supervisor.as_ref().map(|s| format!("supervisor {}", s.display()))
+    }
+}
+
+/// One process in a listing.
+pub fn summary(p: &ProcessInfo) -> Value {
+    json!({
+        "pid": p.pid,
+        "name": p.name,
+        "ports": p.ports,
+        "score": p.score,
+        "cmd": p.cmd.join(" "),
+        "cwd": p.cwd,
+        "uptime_secs": p.uptime().as_secs(),
+        "orphaned": p.is_orphaned(),
+        "owner": owner(p),
+        "terminal": p.pane.as_ref().map(|pane| pane.display()),
+    })
+}
+
+/// The summary plus the score breakdown and everything else that went into it.
+pub fn explain(p: &ProcessInfo, protect: &Protect) -> Value {
+    let factors: Vec<Value> = score_factors(p)
+        .into_iter()
+        .map(|(reason, points)| json!({ "reason": reason, "points": points }))
+        .collect();
+    let mut explanation = summary(p);
+    explanation["likelihood"] = json!(ScoreLevel::of(p.score).label());
+    explanation["score_factors"] = Value::Array(factors);
+    explanation["terminal"] = json!(p.pane.clone().or_else(|| multiplexer::pane_of(p)).map(|pane| pane.display()));
+    explanation["parent"] = json!(p.parent_name.as_ref().map(|name| format!("{} (PID {})", name, p.parent_pid.unwrap_or(0))));
+    explanation["idle"] = json!(p.idle_display());
+    explanation["memory"] = json!(p.memory_display());
+    explanation["project_root"] = json!(p.project_root);
+    explanation["protected"] = json!(protect.is_protected(p));
+    explanation
+}
+
+/// The listening process `pid`. `ccpclean` itself never counts.
+pub fn listening(processes: Vec<ProcessInfo>, pid: u32) -> Result<ProcessInfo, ActionError> {
+    apply_filter(processes, FilterMode::Loose)
+        .into_iter()
+        .find(|p| p.pid == pid && pid != std::process::id())
+        .ok_or(ActionError::NotListening(pid))
+}
+
+/// Sends SIGTERM to `p` unless it is protected, recording the kill in the history. Returns
+/// what was (or, with `dry_run`, would have been) done.
+pub fn kill(p: &ProcessInfo, protect: &Protect, dry_run: bool, trigger: Trigger) -> Result<String, ActionError> {
+    if protect.is_protected(p) {
+        return Err(ActionError::Protected(p.pid, p.name.clone()));
+    }
+    let note = owner(p)
+        .map(|owner| format!(" Note: it is managed by {}, which may restart it.", owner))
+        .unwrap_or_default();
+    if dry_run {
+        return Ok(format!(
+            "Dry run: would send SIGTERM to PID {} ({}) on port {}.{}",
+            p.pid, p.name, p.ports_display(), note
+        ));
+    }
+    let entry = history::kill(p, trigger).map_err(ActionError::Kill)?;
+    Ok(format!("Sent {} to PID {} ({}) on port {}.{}", entry.signal, p.pid, p.name, p.ports_display(), note))
+}
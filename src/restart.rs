@@ -0,0 +1,207 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, ProcessStatus, ProcessesToUpdate, System};
+use crate::history::HistoryEntry;
+use crate::inspect;
+use crate::paths;
+use crate::scanner;
+
+/// How long a relaunch waits for the killed process to exit and release its ports.
+const RELEASE_TIMEOUT: Duration = Duration::from_secs(5);
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The part of the environment of `pid` worth recording for a restart: variables that are
+/// missing from or different in ccpclean's own environment (which usually comes from the same
+/// shell), minus anything that looks like a credential (see `inspect::mask_env_value`).
+pub fn capture_restart_env(pid: u32) -> Vec<String> {
+    let ours: HashSet<String> = std::env::vars_os()
+        .map(|(k, v)| format!("{}={}", k.to_string_lossy(), v.to_string_lossy()))
+        .collect();
+    restart_env(scanner::capture_env(pid), &ours)
+}
+
+fn restart_env(env: Vec<String>, ours: &HashSet<String>) -> Vec<String> {
+    env.into_iter()
+        .filter(|var| !ours.contains(var))
+        .filter(|var| match var.split_once('=') {
+            Some((key, value)) => inspect::mask_env_value(key, value) == value,
+            None => false,
+        })
+        .collect()
+}
+
+fn log_path(entry: &HistoryEntry) -> Option<PathBuf> {
+    paths::state_dir().map(|d| {
+        d.join("restarts")
+            .join(format!("{}-{}-{}.log", entry.name, entry.pid, entry.time))
+    })
+}
+
+/// Builds the command that reproduces a killed process: same argv and working directory, and
+/// our environment with the captured variables applied on top.
+pub fn command_for(entry: &HistoryEntry) -> io::Result<Command> {
+    let (program, args) = entry
+        .cmd
+        .split_first()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no command line was recorded"))?;
+
+    let mut command = Command::new(program);
+    command.args(args);
+    if let Some(ref cwd) = entry.cwd {
+        command.current_dir(cwd);
+    }
+    for var in &entry.env {
+        if let Some((key, value)) = var.split_once('=') {
+            if !key.is_empty() {
+                command.env(key, value);
+            }
+        }
+    }
+    Ok(command)
+}
+
+/// Whether the killed process is still alive. A live PID running something else has been
+/// reused, so it does not count.
+fn still_running(entry: &HistoryEntry) -> bool {
+    let pid = Pid::from_u32(entry.pid);
+    let mut sys = System::new();
+    sys.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+    sys.process(pid).is_some_and(|p| {
+        p.status() != ProcessStatus::Zombie
+            && p.name().to_string_lossy().trim_end_matches(".exe") == entry.name
+    })
+}
+
+/// Waits up to `RELEASE_TIMEOUT` for the killed process to exit and for its ports to be free,
+/// so the relaunched server does not fail to bind (or start on a fallback port).
+fn wait_for_release(entry: &HistoryEntry) -> io::Result<()> {
+    let start = Instant::now();
+    loop {
+        let busy = if still_running(entry) {
+            Some(format!("PID {} is still running", entry.pid))
+        } else {
+            let listening = scanner::listening_ports();
+            entry
+                .ports
+                .iter()
+                .find(|port| listening.contains(port))
+                .map(|port| format!("port {} is still in use", port))
+        };
+        let Some(reason) = busy else { return Ok(()) };
+        if start.elapsed() >= RELEASE_TIMEOUT {
+            return Err(io::Error::new(io::ErrorKind::AddrInUse, reason));
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Relaunches the process recorded in `entry`, detached from this terminal, with stdout and
+/// stderr appended to a log file, once the old process has exited and freed its ports.
+/// Returns the new PID and the log path.
+pub fn relaunch(entry: &HistoryEntry) -> io::Result<(u32, PathBuf)> {
+    let mut command = command_for(entry)?;
+    wait_for_release(entry)?;
+
+    let log = log_path(entry).ok_or_else(|| {
+        io::Error::new(io::ErrorKind::NotFound, "cannot locate the state directory")
+    })?;
+    if let Some(dir) = log.parent() {
+        paths::create_private_dir(dir)?;
+    }
+    let out = File::options().create(true).append(true).open(&log)?;
+    let err = out.try_clone()?;
+    command.stdin(Stdio::null()).stdout(out).stderr(err);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        // Own process group, so Ctrl-C in the launching terminal does not reach it.
+        command.process_group(0);
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const DETACHED_PROCESS: u32 = 0x0000_0008;
+        const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+        command.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+    }
+
+    let child = command.spawn()?;
+    Ok((child.id(), log))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::Trigger;
+
+    fn make_entry(cmd: Vec<&str>, env: Vec<&str>) -> HistoryEntry {
+        HistoryEntry {
+            time: 0,
+            pid: 1,
+            name: "node".to_string(),
+            cmd: cmd.iter().map(|s| s.to_string()).collect(),
+            cwd: Some("/srv/app".to_string()),
+            env: env.iter().map(|s| s.to_string()).collect(),
+            ports: vec![3000],
+            score: 90,
+            signal: "SIGTERM".to_string(),
+            error: None,
+            trigger: Trigger::Tui,
+            user: None,
+        }
+    }
+
+    #[test]
+    fn test_command_for_reproduces_context() {
+        let entry = make_entry(vec!["node", "server.js", "--port", "3000"], vec!["NODE_ENV=dev", "A=b=c"]);
+        let cmd = command_for(&entry).unwrap();
+        assert_eq!(cmd.get_program(), "node");
+        assert_eq!(cmd.get_args().collect::<Vec<_>>(), vec!["server.js", "--port", "3000"]);
+        assert_eq!(cmd.get_current_dir(), Some(std::path::Path::new("/srv/app")));
+        let envs: Vec<_> = cmd.get_envs().collect();
+        assert!(envs.contains(&("NODE_ENV".as_ref(), Some("dev".as_ref()))));
+        assert!(envs.contains(&("A".as_ref(), Some("b=c".as_ref()))));
+    }
+
+    #[test]
+    fn test_restart_env_keeps_only_new_and_changed() {
+        let ours: HashSet<String> = ["PATH=/usr/bin", "HOME=/home/me"].iter().map(|s| s.to_string()).collect();
+        let env = [
+            "PATH=/usr/bin",
+            "HOME=/home/me",
+            "NODE_ENV=dev",
+            "PORT=3000",
+            "GITHUB_TOKEN=ghp_x",
+            "DATABASE_URL=postgres://app:hunter2@db/app",
+            "REDIS_URL=redis://cache:6379",
+        ];
+        let kept = restart_env(env.iter().map(|s| s.to_string()).collect(), &ours);
+        assert_eq!(kept, vec!["NODE_ENV=dev", "PORT=3000", "REDIS_URL=redis://cache:6379"]);
+    }
+
+    #[test]
+    fn test_wait_for_release_when_gone() {
+        let mut entry = make_entry(vec!["node"], vec![]);
+        entry.pid = 99999999;
+        entry.ports = vec![];
+        assert!(wait_for_release(&entry).is_ok());
+    }
+
+    #[test]
+    fn test_command_for_without_env_inherits() {
+        let entry = make_entry(vec!["node"], vec![]);
+        let cmd = command_for(&entry).unwrap();
+        assert_eq!(cmd.get_envs().count(), 0);
+    }
+
+    #[test]
+    fn test_command_for_requires_cmd() {
+        assert!(command_for(&make_entry(vec![], vec![])).is_err());
+    }
+}
@@ -1,13 +1,27 @@
-use std::collections::HashMap;
-use sysinfo::System;
-use crate::process_info::ProcessInfo;
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::Path;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+use crate::container;
+use crate::process_info::{Listener, ProcessInfo};
+use crate::project;
+use crate::supervisor;
+use crate::systemd;
 use crate::filter::is_dev_runtime;
 
+#[derive(Default)]
+struct SocketMaps {
+    /// PID -> listening sockets
+    listening: HashMap<u32, Vec<Listener>>,
+    /// Local port -> number of ESTABLISHED connections
+    established: HashMap<u16, usize>,
+}
+
 #[cfg(not(target_os = "macos"))]
-fn build_port_map() -> HashMap<u32, Vec<u16>> {
+fn build_socket_maps() -> SocketMaps {
     use netstat2::{get_sockets_info, AddressFamilyFlags, ProtocolFlags, ProtocolSocketInfo};
 
-    let mut map: HashMap<u32, Vec<u16>> = HashMap::new();
+    let mut maps = SocketMaps::default();
 
     let af_flags = AddressFamilyFlags::IPV4 | AddressFamilyFlags::IPV6;
     let proto_flags = ProtocolFlags::TCP;
@@ -16,24 +30,30 @@ fn build_port_map() -> HashMap<u32, Vec<u16>> {
         for si in sockets {
             if let ProtocolSocketInfo::Tcp(tcp) = si.protocol_socket_info {
                 use netstat2::TcpState;
-                if tcp.state == TcpState::Listen {
-                    let port = tcp.local_port;
-                    for pid in &si.associated_pids {
-                        map.entry(*pid).or_default().push(port);
+                match tcp.state {
+                    TcpState::Listen => {
+                        let listener = Listener { address: tcp.local_addr, port: tcp.local_port };
+                        for pid in &si.associated_pids {
+                            maps.listening.entry(*pid).or_default().push(listener.clone());
+                        }
+                    }
+                    TcpState::Established => {
+                        *maps.established.entry(tcp.local_port).or_default() += 1;
                     }
+                    _ => {}
                 }
             }
         }
     }
 
-    map
+    maps
 }
 
 #[cfg(target_os = "macos")]
-fn build_port_map() -> HashMap<u32, Vec<u16>> {
+fn build_socket_maps() -> SocketMaps {
     use std::process::Command;
 
-    let mut map: HashMap<u32, Vec<u16>> = HashMap::new();
+    let mut maps = SocketMaps::default();
 
     let output = Command::new("lsof")
         .args(["-iTCP", "-sTCP:LISTEN", "-nP", "-F", "pn"])
@@ -48,30 +68,169 @@ fn build_port_map() -> HashMap<u32, Vec<u16>> {
                 current_pid = pid_str.parse().ok();
             } else if let Some(name) = line.strip_prefix('n') {
                 if let Some(pid) = current_pid {
-                    if let Some(port_str) = name.rsplit(':').next() {
-                        if let Ok(port) = port_str.parse::<u16>() {
-                            map.entry(pid).or_default().push(port);
-                        }
+                    if let Some(listener) = parse_lsof_name(name) {
+                        maps.listening.entry(pid).or_default().push(listener);
                     }
                 }
             }
         }
     }
 
-    map
+    // Established names look like `127.0.0.1:3000->127.0.0.1:52814`; count by local port.
+    let output = Command::new("lsof")
+        .args(["-iTCP", "-sTCP:ESTABLISHED", "-nP", "-F", "n"])
+        .output();
+
+    if let Ok(output) = output {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        for line in stdout.lines() {
+            if let Some(name) = line.strip_prefix('n') {
+                let local = name.split("->").next().unwrap_or("");
+                if let Some(Ok(port)) = local.rsplit(':').next().map(str::parse::<u16>) {
+                    *maps.established.entry(port).or_default() += 1;
+                }
+            }
+        }
+    }
+
+    maps
+}
+
+/// Parses an lsof socket name such as `*:3000`, `127.0.0.1:3000` or `[::1]:3000`.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_lsof_name(name: &str) -> Option<Listener> {
+    let (addr, port) = name.rsplit_once(':')?;
+    let port = port.parse().ok()?;
+    let addr = addr.trim_start_matches('[').trim_end_matches(']');
+    // Drop an IPv6 zone such as `%lo0`.
+    let addr = addr.split('%').next().unwrap_or(addr);
+    let address = if addr == "*" {
+        IpAddr::V4(Ipv4Addr::UNSPECIFIED)
+    } else {
+        addr.parse().ok()?
+    };
+    Some(Listener { address, port })
+}
+
+/// Parses an established lsof socket name such as `127.0.0.1:3000->127.0.0.1:52814` into the
+/// local port and the peer address.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_lsof_connection(name: &str) -> Option<(u16, SocketAddr)> {
+    let (local, remote) = name.split_once("->")?;
+    let local = parse_lsof_name(local)?;
+    let remote = parse_lsof_name(remote)?;
+    Some((local.port, SocketAddr::new(remote.address, remote.port)))
+}
+
+/// Peers of the ESTABLISHED TCP connections on `ports`, as `(local port, peer)`.
+#[cfg(not(target_os = "macos"))]
+pub fn established_peers(ports: &[u16]) -> Vec<(u16, SocketAddr)> {
+    use netstat2::{get_sockets_info, AddressFamilyFlags, ProtocolFlags, ProtocolSocketInfo, TcpState};
+
+    let af_flags = AddressFamilyFlags::IPV4 | AddressFamilyFlags::IPV6;
+    let Ok(sockets) = get_sockets_info(af_flags, ProtocolFlags::TCP) else {
+        return Vec::new();
+    };
+    sockets
+        .into_iter()
+        .filter_map(|si| match si.protocol_socket_info {
+            ProtocolSocketInfo::Tcp(tcp)
+                if tcp.state == TcpState::Established && ports.contains(&tcp.local_port) =>
+            {
+                Some((tcp.local_port, SocketAddr::new(tcp.remote_addr, tcp.remote_port)))
+            }
+            _ => None,
+        })
+        .collect()
 }
 
+#[cfg(target_os = "macos")]
+pub fn established_peers(ports: &[u16]) -> Vec<(u16, SocketAddr)> {
+    use std::process::Command;
+
+    let Ok(output) = Command::new("lsof")
+        .args(["-iTCP", "-sTCP:ESTABLISHED", "-nP", "-F", "n"])
+        .output()
+    else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| parse_lsof_connection(line.strip_prefix('n')?))
+        .filter(|(port, _)| ports.contains(port))
+        .collect()
+}
+
+/// Total CPU time from `/proc/<pid>/stat` (utime + stime).
+#[cfg(target_os = "linux")]
+fn cpu_time_ms(pid: u32) -> Option<u64> {
+    // SAFETY: sysconf has no preconditions.
+    let ticks_per_sec = u64::try_from(unsafe { libc::sysconf(libc::_SC_CLK_TCK) }).ok().filter(|&t| t > 0)?;
+
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // The command name may contain spaces, so split after its closing paren.
+    let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    Some((utime + stime) * 1000 / ticks_per_sec)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn cpu_time_ms(_pid: u32) -> Option<u64> {
+    None
+}
+
+/// ESTABLISHED TCP connections per local port, for sampling between full scans.
+pub fn connection_counts() -> HashMap<u16, usize> {
+    build_socket_maps().established
+}
+
+/// Reads the environment of `pid` as `KEY=VALUE` strings. Returns an empty list when the
+/// platform or permissions do not allow it.
+pub fn capture_env(pid: u32) -> Vec<String> {
+    let pid = Pid::from_u32(pid);
+    let mut sys = System::new();
+    sys.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        true,
+        ProcessRefreshKind::new().with_environ(UpdateKind::Always),
+    );
+    sys.process(pid)
+        .map(|p| p.environ().iter().map(|s| s.to_string_lossy().to_string()).collect())
+        .unwrap_or_default()
+}
+
+/// Ports that currently have a listening TCP socket.
+pub fn listening_ports() -> HashSet<u16> {
+    build_socket_maps().listening.values().flatten().map(|l| l.port).collect()
+}
+
+/// Every process on the system with its listening ports, connections and owner (container,
+/// systemd unit, supervisor, terminal pane). Scores are left at 0; see `filter::compute_score`.
 pub fn scan() -> Vec<ProcessInfo> {
     let mut sys = System::new_all();
     sys.refresh_all();
 
-    let port_map = build_port_map();
+    let sockets = build_socket_maps();
 
     let mut results = Vec::new();
+    // Many processes share a working directory; look each one up once.
+    let mut roots: HashMap<String, String> = HashMap::new();
 
     for (pid, process) in sys.processes() {
         let pid_u32 = pid.as_u32();
-        let ports = port_map.get(&pid_u32).cloned().unwrap_or_default();
+        let listeners = sockets.listening.get(&pid_u32).cloned().unwrap_or_default();
+        // IPv4 and IPv6 sockets on the same port count once.
+        let mut ports: Vec<u16> = Vec::new();
+        for l in &listeners {
+            if !ports.contains(&l.port) {
+                ports.push(l.port);
+            }
+        }
+        let connections = ports
+            .iter()
+            .map(|port| sockets.established.get(port).copied().unwrap_or(0))
+            .sum();
 
         let name = process.name().to_string_lossy().to_string();
         let name = name.trim_end_matches(".exe").to_string();
@@ -82,6 +241,14 @@ pub fn scan() -> Vec<ProcessInfo> {
             .map(|s| s.to_string_lossy().to_string())
             .collect();
 
+        let cwd = process.cwd().map(|d| d.to_string_lossy().to_string());
+        let project_root = cwd.as_ref().map(|dir| {
+            roots
+                .entry(dir.clone())
+                .or_insert_with(|| project::project_root(Path::new(dir)).to_string_lossy().to_string())
+                .clone()
+        });
+
         let parent_pid = process.parent().map(|p| p.as_u32());
         let parent_name = parent_pid.and_then(|ppid| {
             sys.process(sysinfo::Pid::from_u32(ppid))
@@ -95,21 +262,80 @@ pub fn scan() -> Vec<ProcessInfo> {
 
         let is_dev = is_dev_runtime(&name);
 
+        #[cfg(unix)]
+        let uid = process.user_id().map(|uid| **uid);
+        #[cfg(not(unix))]
+        let uid = None;
+
+        // Only listening processes are scored, so only they need their unit.
+        let unit = if ports.is_empty() { None } else { systemd::unit_of(pid_u32) };
+
         let info = ProcessInfo {
             pid: pid_u32,
             name,
             cmd,
+            cwd,
+            project_root,
             ports,
+            listeners,
             start_time_secs: process.start_time(),
             memory_kb: process.memory() / 1024,
             parent_pid,
             parent_name,
+            uid,
             is_dev_runtime: is_dev,
             score: 0,
+            cpu_time_ms: cpu_time_ms(pid_u32),
+            connections,
+            idle_secs: None,
+            stopped: process.status() == sysinfo::ProcessStatus::Stop,
+            container: None,
+            unit,
+            supervisor: None,
+            pane: None,
         };
 
         results.push(info);
     }
 
+    container::annotate(&mut results);
+    supervisor::annotate(&mut results);
     results
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv6Addr;
+
+    #[test]
+    fn test_parse_lsof_name() {
+        assert_eq!(
+            parse_lsof_name("*:3000"),
+            Some(Listener { address: IpAddr::V4(Ipv4Addr::UNSPECIFIED), port: 3000 })
+        );
+        assert_eq!(
+            parse_lsof_name("127.0.0.1:5173"),
+            Some(Listener { address: IpAddr::V4(Ipv4Addr::LOCALHOST), port: 5173 })
+        );
+        assert_eq!(
+            parse_lsof_name("[::1]:8080"),
+            Some(Listener { address: IpAddr::V6(Ipv6Addr::LOCALHOST), port: 8080 })
+        );
+        assert_eq!(parse_lsof_name("[fe80::1%lo0]:80").map(|l| l.port), Some(80));
+        assert_eq!(parse_lsof_name("localhost:http"), None);
+    }
+
+    #[test]
+    fn test_parse_lsof_connection() {
+        assert_eq!(
+            parse_lsof_connection("127.0.0.1:3000->127.0.0.1:52814"),
+            Some((3000, "127.0.0.1:52814".parse().unwrap()))
+        );
+        assert_eq!(
+            parse_lsof_connection("[::1]:8080->[::1]:61000"),
+            Some((8080, "[::1]:61000".parse().unwrap()))
+        );
+        assert_eq!(parse_lsof_connection("*:3000"), None);
+    }
+}
@@ -0,0 +1,557 @@
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use crate::config::Protect;
+use crate::filter::{apply_filter, FilterMode, ScoreLevel};
+use crate::history::Trigger;
+use crate::paths;
+use crate::process_info::ProcessInfo;
+use crate::report::{self, summary, ActionError};
+
+pub const DEFAULT_LISTEN: &str = "127.0.0.1:7357";
+
+const TICK: Duration = Duration::from_millis(200);
+/// A client that stalls mid-request is dropped after this long.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_HEADER_BYTES: u64 = 16 * 1024;
+
+/// Where `serve` accepts connections.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Listen {
+    Tcp(SocketAddr),
+    Unix(PathBuf),
+}
+
+impl std::fmt::Display for Listen {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Listen::Tcp(addr) => write!(f, "http://{}", addr),
+            Listen::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ServeError {
+    /// Only loopback addresses are allowed: the API can kill processes.
+    NotLoopback(SocketAddr),
+    UnixUnsupported,
+    /// `--socket` names an existing file that is not a socket; it is left alone.
+    NotSocket(PathBuf),
+    NoStateDir,
+    Io(io::Error),
+}
+
+impl std::fmt::Display for ServeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ServeError::NotLoopback(addr) => {
+                write!(f, "Refusing to listen on {}: use a loopback address such as 127.0.0.1", addr)
+            }
+            ServeError::UnixUnsupported => write!(f, "Unix sockets are not supported on this platform"),
+            ServeError::NotSocket(path) => {
+                write!(f, "Refusing to replace {}: it exists and is not a socket", path.display())
+            }
+            ServeError::NoStateDir => {
+                write!(f, "Cannot locate the state directory for the token (set HOME or XDG_STATE_HOME)")
+            }
+            ServeError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<io::Error> for ServeError {
+    fn from(e: io::Error) -> Self {
+        ServeError::Io(e)
+    }
+}
+
+/// Written to `serve.json` while a server runs, so editor plugins can find it and scans can
+/// leave it out.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ServerInfo {
+    pub pid: u32,
+    /// `http://127.0.0.1:7357` or `unix:/path/to/socket`.
+    pub listen: String,
+}
+
+/// The bearer token clients must send, kept across runs so plugins are configured once.
+pub fn token_file() -> Option<PathBuf> {
+    paths::state_dir().map(|d| d.join("serve.token"))
+}
+
+pub fn info_file() -> Option<PathBuf> {
+    paths::state_dir().map(|d| d.join("serve.json"))
+}
+
+/// PID of the running `ccpclean serve`, if any.
+pub fn running_pid() -> Option<u32> {
+    let info: ServerInfo = serde_json::from_str(&fs::read_to_string(info_file()?).ok()?).ok()?;
+    Some(info.pid)
+}
+
+/// Leaves out a running `ccpclean serve`: it listens on a local port too, but is not ours to
+/// clean up. For callers that scan on behalf of the user (the TUI, watch, the daemon).
+pub fn exclude_running(processes: &mut Vec<ProcessInfo>) {
+    if let Some(pid) = running_pid() {
+        processes.retain(|p| !(p.pid == pid && p.name.starts_with("ccpclean")));
+    }
+}
+
+/// 256 bits from the operating system's random number generator, as hex.
+fn generate_token() -> io::Result<String> {
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes).map_err(|e| io::Error::other(e.to_string()))?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Reads the token at `path`, creating it on first use. The file is kept readable by the
+/// owner only, including one created with looser permissions.
+pub fn load_or_create_token(path: &Path) -> io::Result<String> {
+    match fs::read_to_string(path) {
+        Ok(token) if !token.trim().is_empty() => {
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+            }
+            return Ok(token.trim().to_string());
+        }
+        Ok(_) => {}
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    let token = generate_token()?;
+    paths::write_private(path, &format!("{}\n", token))?;
+    Ok(token)
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub query: Vec<(String, String)>,
+    /// From `Authorization: Bearer <token>`.
+    pub token: Option<String>,
+}
+
+impl Request {
+    fn flag(&self, name: &str) -> bool {
+        self.query.iter().any(|(k, v)| k == name && matches!(v.as_str(), "" | "1" | "true"))
+    }
+}
+
+/// Reads the request line and headers; the body, if any, is skipped.
+fn read_request(reader: &mut impl BufRead) -> io::Result<Request> {
+    let mut reader = reader.take(MAX_HEADER_BYTES);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "malformed request line"));
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let mut request = Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        query: query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (k, v) = pair.split_once('=').unwrap_or((pair, ""));
+                (k.to_string(), v.to_string())
+            })
+            .collect(),
+        token: None,
+    };
+    let mut content_length = 0;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        let Some((name, value)) = line.split_once(':') else { continue };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("authorization") {
+            request.token = value.strip_prefix("Bearer ").map(|t| t.trim().to_string());
+        } else if name.eq_ignore_ascii_case("content-length") {
+            content_length = value.parse().unwrap_or(0);
+        }
+    }
+    let reader = reader.into_inner();
+    io::copy(&mut reader.by_ref().take(content_length.min(MAX_HEADER_BYTES)), &mut io::sink())?;
+    Ok(request)
+}
+
+/// Compares without stopping at the first difference, so response times do not leak how much
+/// of a guessed token was right.
+fn token_matches(given: &str, expected: &str) -> bool {
+    given.len() == expected.len() && given.bytes().zip(expected.bytes()).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    }
+}
+
+fn write_response(w: &mut impl Write, status: u16, body: &Value) -> io::Result<()> {
+    let body = body.to_string();
+    write!(
+        w,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason(status),
+        body.len(),
+        body
+    )?;
+    w.flush()
+}
+
+fn error(status: u16, message: impl std::fmt::Display) -> (u16, Value) {
+    (status, json!({ "error": message.to_string() }))
+}
+
+/// The JSON API over the processes `collect` scans.
+pub struct Api<'a, F> {
+    protect: &'a Protect,
+    /// Never kill, whatever a request asks.
+    dry_run: bool,
+    token: String,
+    collect: F,
+}
+
+impl<'a, F> Api<'a, F>
+where
+    F: FnMut() -> Vec<ProcessInfo>,
+{
+    /// `collect` returns every scored process, listening or not; the routes filter it.
+    pub fn new(protect: &'a Protect, dry_run: bool, token: String, collect: F) -> Self {
+        Self { protect, dry_run, token, collect }
+    }
+
+    /// `GET /status`, `GET /processes[?all]`, `GET /processes/<pid>` and
+    /// `POST /processes/<pid>/kill[?dry_run]`, each answered with a status and a JSON body.
+    pub fn handle(&mut self, req: &Request) -> (u16, Value) {
+        if !req.token.as_deref().is_some_and(|t| token_matches(t, &self.token)) {
+            return error(401, "missing or invalid token (send `Authorization: Bearer <token>`)");
+        }
+        let segments: Vec<&str> = req.path.split('/').filter(|s| !s.is_empty()).collect();
+        let method = req.method.as_str();
+        match (method, segments.as_slice()) {
+            ("GET", ["status"]) => {
+                let servers = apply_filter((self.collect)(), FilterMode::Strict);
+                let stale = servers.iter().filter(|p| ScoreLevel::of(p.score) == ScoreLevel::High).count();
+                (200, json!({ "version": env!("CARGO_PKG_VERSION"), "dev_servers": servers.len(), "stale": stale }))
+            }
+            ("GET", ["processes"]) => {
+                let mode = if req.flag("all") { FilterMode::Loose } else { FilterMode::Strict };
+                let mut processes = apply_filter((self.collect)(), mode);
+                processes.sort_by_key(|p| std::cmp::Reverse(p.score));
+                (200, Value::Array(processes.iter().map(summary).collect()))
+            }
+            ("GET", ["processes", pid]) => match self.find(pid) {
+                Ok(p) => (200, report::explain(&p, self.protect)),
+                Err(e) => e,
+            },
+            ("POST", ["processes", pid, "kill"]) => {
+                let p = match self.find(pid) {
+                    Ok(p) => p,
+                    Err(e) => return e,
+                };
+                match report::kill(&p, self.protect, self.dry_run || req.flag("dry_run"), Trigger::Api) {
+                    Ok(message) => (200, json!({ "message": message })),
+                    Err(e @ ActionError::Protected(..)) => error(403, e),
+                    Err(e) => error(500, e),
+                }
+            }
+            (_, ["status"] | ["processes"] | ["processes", _] | ["processes", _, "kill"]) => {
+                error(405, format!("{} is not allowed on {}", method, req.path))
+            }
+            _ => error(404, format!("no route for {}", req.path)),
+        }
+    }
+
+    fn find(&mut self, pid: &str) -> Result<ProcessInfo, (u16, Value)> {
+        let pid = pid.parse().map_err(|_| error(400, format!("invalid PID: {}", pid)))?;
+        report::listening((self.collect)(), pid).map_err(|e| error(404, e))
+    }
+
+    /// Answers the one request on `stream`.
+    pub fn serve_connection<S: Read + Write>(&mut self, stream: &mut S) -> io::Result<()> {
+        let request = read_request(&mut BufReader::new(&mut *stream));
+        let (status, body) = match request {
+            Ok(ref request) => self.handle(request),
+            Err(e) => error(400, e),
+        };
+        write_response(stream, status, &body)
+    }
+}
+
+trait Stream: Read + Write {}
+impl<T: Read + Write> Stream for T {}
+
+enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(std::os::unix::net::UnixListener),
+}
+
+impl Listener {
+    fn bind(listen: &Listen) -> Result<Self, ServeError> {
+        match listen {
+            Listen::Tcp(addr) if !addr.ip().is_loopback() => Err(ServeError::NotLoopback(*addr)),
+            Listen::Tcp(addr) => {
+                let listener = TcpListener::bind(addr)?;
+                listener.set_nonblocking(true)?;
+                Ok(Listener::Tcp(listener))
+            }
+            #[cfg(unix)]
+            Listen::Unix(path) => {
+                use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+                use std::os::unix::net::{UnixListener, UnixStream};
+                match fs::symlink_metadata(path) {
+                    Ok(meta) if !meta.file_type().is_socket() => return Err(ServeError::NotSocket(path.clone())),
+                    // A socket file left by a server that is no longer running.
+                    Ok(_) if UnixStream::connect(path).is_err() => fs::remove_file(path)?,
+                    _ => {}
+                }
+                let listener = UnixListener::bind(path)?;
+                fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+                listener.set_nonblocking(true)?;
+                Ok(Listener::Unix(listener))
+            }
+            #[cfg(not(unix))]
+            Listen::Unix(_) => Err(ServeError::UnixUnsupported),
+        }
+    }
+
+    fn accept(&self) -> io::Result<Box<dyn Stream>> {
+        match self {
+            Listener::Tcp(listener) => {
+                let (stream, _) = listener.accept()?;
+                stream.set_nonblocking(false)?;
+                stream.set_read_timeout(Some(READ_TIMEOUT))?;
+                Ok(Box::new(stream))
+            }
+            #[cfg(unix)]
+            Listener::Unix(listener) => {
+                let (stream, _) = listener.accept()?;
+                stream.set_nonblocking(false)?;
+                stream.set_read_timeout(Some(READ_TIMEOUT))?;
+                Ok(Box::new(stream))
+            }
+        }
+    }
+}
+
+/// Serves the API on `listen` until SIGTERM/Ctrl-C, one request at a time.
+pub fn run<F>(listen: &Listen, protect: &Protect, dry_run: bool, mut collect: F) -> Result<(), ServeError>
+where
+    F: FnMut() -> Vec<ProcessInfo>,
+{
+    let token_path = token_file().ok_or(ServeError::NoStateDir)?;
+    let token = load_or_create_token(&token_path)?;
+    let listener = Listener::bind(listen)?;
+
+    let stop = Arc::new(AtomicBool::new(false));
+    {
+        let stop = Arc::clone(&stop);
+        ctrlc::set_handler(move || stop.store(true, Ordering::SeqCst)).map_err(io::Error::other)?;
+    }
+
+    let info_path = info_file();
+    if let Some(ref path) = info_path {
+        let info = ServerInfo { pid: std::process::id(), listen: listen.to_string() };
+        paths::write_private(path, &serde_json::to_string(&info).map_err(io::Error::other)?)?;
+    }
+    eprintln!("Listening on {} (token in {})", listen, token_path.display());
+
+    let self_pid = std::process::id();
+    let mut api = Api::new(protect, dry_run, token, || {
+        let mut processes = collect();
+        processes.retain(|p| p.pid != self_pid);
+        processes
+    });
+    while !stop.load(Ordering::SeqCst) {
+        match listener.accept() {
+            Ok(mut stream) => {
+                if let Err(e) = api.serve_connection(&mut stream) {
+                    eprintln!("Request failed: {}", e);
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => thread::sleep(TICK),
+            Err(e) => eprintln!("Accept failed: {}", e),
+        }
+    }
+
+    if let Some(ref path) = info_path {
+        let _ = fs::remove_file(path);
+    }
+    if let Listen::Unix(ref path) = listen {
+        let _ = fs::remove_file(path);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_proc(pid: u32, name: &str, port: u16, score: u8) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            name: name.to_string(),
+            cmd: vec![name.to_string(), "server.js".to_string()],
+            ports: vec![port],
+            start_time_secs: crate::clock::now_secs() - 3600,
+            is_dev_runtime: name == "node",
+            score,
+            ..Default::default()
+        }
+    }
+
+    fn processes() -> Vec<ProcessInfo> {
+        vec![make_proc(4242, "node", 3000, 90), make_proc(4343, "node", 5173, 40), make_proc(4444, "nginx", 8080, 20)]
+    }
+
+    fn request(method: &str, path: &str) -> Request {
+        let raw = format!("{} {} HTTP/1.1\r\nHost: localhost\r\nAuthorization: Bearer secret\r\n\r\n", method, path);
+        read_request(&mut raw.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_read_request() {
+        let raw = "POST /processes/42/kill?dry_run=1 HTTP/1.1\r\nauthorization: Bearer abc\r\nContent-Length: 2\r\n\r\n{}";
+        let req = read_request(&mut raw.as_bytes()).unwrap();
+        assert_eq!(req.method, "POST");
+        assert_eq!(req.path, "/processes/42/kill");
+        assert!(req.flag("dry_run"));
+        assert_eq!(req.token.as_deref(), Some("abc"));
+        assert!(read_request(&mut "\r\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_requires_token() {
+        let protect = Protect::default();
+        let mut api = Api::new(&protect, false, "secret".to_string(), processes);
+        let mut req = request("GET", "/status");
+        req.token = Some("guess".to_string());
+        assert_eq!(api.handle(&req).0, 401);
+        req.token = None;
+        assert_eq!(api.handle(&req).0, 401);
+    }
+
+    #[test]
+    fn test_routes() {
+        let protect = Protect { ports: vec![5173], ..Default::default() };
+        let mut api = Api::new(&protect, false, "secret".to_string(), processes);
+        assert_eq!(api.handle(&request("GET", "/status")), (200, json!({ "version": env!("CARGO_PKG_VERSION"), "dev_servers": 2, "stale": 1 })));
+        let (_, all) = api.handle(&request("GET", "/processes?all=true"));
+        assert_eq!(all.as_array().unwrap().len(), 3);
+        let (status, explained) = api.handle(&request("GET", "/processes/4242"));
+        assert_eq!(status, 200);
+        assert!(explained["score_factors"].as_array().is_some_and(|f| !f.is_empty()));
+        assert_eq!(api.handle(&request("GET", "/processes/1")).0, 404);
+        assert_eq!(api.handle(&request("GET", "/processes/abc")).0, 400);
+        assert_eq!(api.handle(&request("DELETE", "/processes/4242")).0, 405);
+        assert_eq!(api.handle(&request("GET", "/nope")).0, 404);
+
+        assert_eq!(api.handle(&request("POST", "/processes/5173/kill")).0, 404);
+        assert_eq!(api.handle(&request("POST", "/processes/4343/kill")).0, 403);
+        let (status, body) = api.handle(&request("POST", "/processes/4242/kill?dry_run"));
+        assert_eq!(status, 200);
+        assert!(body["message"].as_str().unwrap().starts_with("Dry run"));
+    }
+
+    struct Duplex {
+        input: io::Cursor<Vec<u8>>,
+        output: Vec<u8>,
+    }
+
+    impl Read for Duplex {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.input.read(buf)
+        }
+    }
+
+    impl Write for Duplex {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.output.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_serve_connection() {
+        let protect = Protect::default();
+        let mut api = Api::new(&protect, true, "secret".to_string(), processes);
+        let mut stream = Duplex {
+            input: io::Cursor::new(b"GET /status HTTP/1.1\r\nAuthorization: Bearer secret\r\n\r\n".to_vec()),
+            output: Vec::new(),
+        };
+        api.serve_connection(&mut stream).unwrap();
+        let response = String::from_utf8(stream.output).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("Content-Type: application/json\r\n"));
+        assert!(response.contains("\"stale\":1"));
+    }
+
+    #[test]
+    fn test_rejects_public_address() {
+        let addr: SocketAddr = "0.0.0.0:7357".parse().unwrap();
+        assert!(matches!(Listener::bind(&Listen::Tcp(addr)), Err(ServeError::NotLoopback(_))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_socket_path_must_be_a_socket() {
+        let path = std::env::temp_dir().join(format!("ccpclean-not-a-socket-{}", std::process::id()));
+        fs::write(&path, "keep me").unwrap();
+        let result = Listener::bind(&Listen::Unix(path.clone()));
+        let kept = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(result, Err(ServeError::NotSocket(_))));
+        assert_eq!(kept, "keep me");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_token_file_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+        let path = std::env::temp_dir().join(format!("ccpclean-token-mode-{}", std::process::id()));
+        fs::write(&path, "abc\n").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        assert_eq!(load_or_create_token(&path).unwrap(), "abc");
+        let mode = fs::metadata(&path).unwrap().permissions().mode() & 0o777;
+        fs::remove_file(&path).unwrap();
+        assert_eq!(mode, 0o600);
+    }
+
+    #[test]
+    fn test_token_is_created_once() {
+        let path = std::env::temp_dir().join(format!("ccpclean-token-test-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let token = load_or_create_token(&path).unwrap();
+        assert_eq!(token.len(), 64);
+        assert!(token.bytes().all(|b| b.is_ascii_hexdigit()));
+        assert_eq!(load_or_create_token(&path).unwrap(), token);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use crate::process_info::ProcessInfo;
+
+/// Ancestors further up than this are not considered the process's supervisor.
+const MAX_DEPTH: usize = 8;
+
+/// File watchers and process managers that restart their child when it exits.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Kind {
+    Pm2,
+    Nodemon,
+    CargoWatch,
+    Air,
+    Watchexec,
+}
+
+impl Kind {
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::Pm2 => "pm2",
+            Kind::Nodemon => "nodemon",
+            Kind::CargoWatch => "cargo watch",
+            Kind::Air => "air",
+            Kind::Watchexec => "watchexec",
+        }
+    }
+
+    /// Recognises a supervisor by process name, or by command line for the ones that run
+    /// under an interpreter (`node .../nodemon`, `PM2 v5.3.0: God Daemon`).
+    fn detect(p: &ProcessInfo) -> Option<Kind> {
+        let script = |name: &str| {
+            p.cmd.iter().take(2).any(|arg| {
+                Path::new(arg).file_stem().is_some_and(|f| f.to_string_lossy() == name)
+            })
+        };
+        match p.name.as_str() {
+            "cargo-watch" => return Some(Kind::CargoWatch),
+            "air" if is_air(p) => return Some(Kind::Air),
+            "watchexec" => return Some(Kind::Watchexec),
+            "nodemon" => return Some(Kind::Nodemon),
+            _ => {}
+        }
+        if p.name.starts_with("PM2") || p.cmd.first().is_some_and(|c| c.starts_with("PM2 v")) {
+            Some(Kind::Pm2)
+        } else if script("nodemon") {
+            Some(Kind::Nodemon)
+        } else if p.name == "cargo" && p.cmd.get(1).is_some_and(|a| a == "watch") {
+            Some(Kind::CargoWatch)
+        } else {
+            None
+        }
+    }
+}
+
+/// `air` is too common a name to go by alone: it must be pointed at a config (`air -c
+/// .air.toml`), be given build flags (`air --build.cmd ...`) or run where an `.air.toml` is.
+fn is_air(p: &ProcessInfo) -> bool {
+    let flags = p.cmd.iter().skip(1).any(|a| a == "-c" || a.starts_with("--build.") || a.ends_with("air.toml"));
+    let config = p.cwd.as_ref().is_some_and(|cwd| {
+        [".air.toml", "air.toml"].iter().any(|f| Path::new(cwd).join(f).is_file())
+    });
+    flags || config
+}
+
+/// An app in the local pm2 daemon.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pm2App {
+    pub name: String,
+    pub id: u32,
+}
+
+/// The supervisor that would respawn a process if it were killed.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Supervisor {
+    pub kind: Kind,
+    pub pid: u32,
+    pub name: String,
+    pub cmd: Vec<String>,
+    pub cwd: Option<String>,
+    /// For pm2, the app the process belongs to (from `~/.pm2/pids`).
+    pub pm2_app: Option<Pm2App>,
+}
+
+impl Supervisor {
+    /// `nodemon (PID 1234)`, `pm2 app api (id 0)`.
+    pub fn display(&self) -> String {
+        match self.pm2_app {
+            Some(ref app) => format!("pm2 app {} (id {})", app.name, app.id),
+            None => format!("{} (PID {})", self.kind.name(), self.pid),
+        }
+    }
+
+    /// The supervisor as a process to kill, carrying the child's ports so the kill history
+    /// (and undo) records what it was serving.
+    pub fn as_process(&self, child: &ProcessInfo) -> ProcessInfo {
+        ProcessInfo {
+            pid: self.pid,
+            name: self.name.clone(),
+            cmd: self.cmd.clone(),
+            cwd: self.cwd.clone(),
+            ports: child.ports.clone(),
+            listeners: child.listeners.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+fn pm2_home() -> Option<PathBuf> {
+    env::var_os("PM2_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".pm2")))
+}
+
+/// `api-0.pid` → `("api", 0)`.
+fn parse_pid_file_name(file: &str) -> Option<(String, u32)> {
+    let (name, id) = file.strip_suffix(".pid")?.rsplit_once('-')?;
+    Some((name.to_string(), id.parse().ok()?))
+}
+
+/// PID → app for every app the local pm2 daemon runs, from the PID files it keeps in
+/// `$PM2_HOME/pids`.
+fn pm2_apps(home: &Path) -> HashMap<u32, Pm2App> {
+    let Ok(dir) = fs::read_dir(home.join("pids")) else {
+        return HashMap::new();
+    };
+    dir.flatten()
+        .filter_map(|entry| {
+            let (name, id) = parse_pid_file_name(&entry.file_name().to_string_lossy())?;
+            let pid = fs::read_to_string(entry.path()).ok()?.trim().parse().ok()?;
+            Some((pid, Pm2App { name, id }))
+        })
+        .collect()
+}
+
+/// Finds the nearest supervisor among the ancestors of every listening process.
+pub fn annotate(processes: &mut [ProcessInfo]) {
+    let by_pid: HashMap<u32, usize> = processes.iter().enumerate().map(|(i, p)| (p.pid, i)).collect();
+    let mut pm2: Option<HashMap<u32, Pm2App>> = None;
+
+    for i in 0..processes.len() {
+        if processes[i].ports.is_empty() {
+            continue;
+        }
+        // The process and its ancestors up to the supervisor, nearest first.
+        let mut chain = vec![processes[i].pid];
+        let mut parent = processes[i].parent_pid;
+        let mut found = None;
+        while let Some(&j) = parent.and_then(|pid| by_pid.get(&pid)) {
+            if chain.len() > MAX_DEPTH || chain.contains(&processes[j].pid) {
+                break;
+            }
+            if let Some(kind) = Kind::detect(&processes[j]) {
+                found = Some((kind, j));
+                break;
+            }
+            chain.push(processes[j].pid);
+            parent = processes[j].parent_pid;
+        }
+        let Some((kind, j)) = found else { continue };
+
+        let pm2_app = if kind == Kind::Pm2 {
+            let apps = pm2.get_or_insert_with(|| pm2_home().map(|h| pm2_apps(&h)).unwrap_or_default());
+            chain.iter().find_map(|pid| apps.get(pid).cloned())
+        } else {
+            None
+        };
+        let s = &processes[j];
+        processes[i].supervisor = Some(Supervisor {
+            kind,
+            pid: s.pid,
+            name: s.name.clone(),
+            cmd: s.cmd.clone(),
+            cwd: s.cwd.clone(),
+            pm2_app,
+        });
+    }
+}
+
+/// `pm2 stop <id>`: stops the app; it stays in pm2's list and is not restarted until started
+/// again.
+pub fn pm2_stop(app: &Pm2App) -> Result<(), String> {
+    pm2(app, "stop")
+}
+
+/// `pm2 delete <id>`: stops the app and removes it from pm2 so it is not restarted.
+pub fn pm2_delete(app: &Pm2App) -> Result<(), String> {
+    pm2(app, "delete")
+}
+
+fn pm2(app: &Pm2App, command: &str) -> Result<(), String> {
+    let output = Command::new("pm2")
+        .args([command, &app.id.to_string()])
+        .output()
+        .map_err(|e| format!("Failed to run pm2: {}", e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!("pm2 {} failed: {}", command, String::from_utf8_lossy(&output.stderr).trim()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proc(pid: u32, parent: u32, name: &str, cmd: &[&str], ports: Vec<u16>) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            parent_pid: Some(parent),
+            name: name.to_string(),
+            cmd: cmd.iter().map(|s| s.to_string()).collect(),
+            ports,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_detect() {
+        let detect = |name: &str, cmd: &[&str]| Kind::detect(&proc(1, 0, name, cmd, Vec::new()));
+        assert_eq!(detect("node", &["node", "/usr/lib/node_modules/nodemon/bin/nodemon.js"]), Some(Kind::Nodemon));
+        assert_eq!(detect("node", &["node", "server.js"]), None);
+        assert_eq!(detect("node", &["node", "/usr/local/bin/nodemon", "app.js"]), Some(Kind::Nodemon));
+        assert_eq!(detect("PM2 v5.3.0: God", &["PM2 v5.3.0: God Daemon (/home/u/.pm2)"]), Some(Kind::Pm2));
+        assert_eq!(detect("cargo", &["cargo", "watch", "-x", "run"]), Some(Kind::CargoWatch));
+        assert_eq!(detect("cargo", &["cargo", "run"]), None);
+        assert_eq!(detect("air", &["air"]), None);
+        assert_eq!(detect("air", &["air", "-c", ".air.toml"]), Some(Kind::Air));
+        assert_eq!(detect("air", &["air", "--build.cmd", "go build -o ./tmp/main ."]), Some(Kind::Air));
+
+        let dir = env::temp_dir().join(format!("ccpclean-air-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(".air.toml"), "root = \".\"\n").unwrap();
+        let air = ProcessInfo { cwd: Some(dir.to_string_lossy().into_owned()), ..proc(1, 0, "air", &["air"], Vec::new()) };
+        let detected = Kind::detect(&air);
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(detected, Some(Kind::Air));
+    }
+
+    #[test]
+    fn test_annotate_finds_nearest_supervisor() {
+        let mut processes = vec![
+            proc(10, 1, "bash", &["bash"], Vec::new()),
+            proc(20, 10, "node", &["node", "/usr/local/bin/nodemon", "app.js"], Vec::new()),
+            proc(30, 20, "sh", &["sh", "-c", "node app.js"], Vec::new()),
+            proc(40, 30, "node", &["node", "app.js"], vec![3000]),
+            proc(50, 10, "node", &["node", "other.js"], vec![4000]),
+        ];
+        annotate(&mut processes);
+        let supervisor = processes[3].supervisor.as_ref().unwrap();
+        assert_eq!((supervisor.kind, supervisor.pid), (Kind::Nodemon, 20));
+        assert_eq!(supervisor.display(), "nodemon (PID 20)");
+        assert_eq!(supervisor.as_process(&processes[3]).ports, vec![3000]);
+        assert!(processes[4].supervisor.is_none());
+    }
+
+    #[test]
+    fn test_pm2_apps_from_pid_files() {
+        assert_eq!(parse_pid_file_name("my-api-3.pid"), Some(("my-api".to_string(), 3)));
+        assert_eq!(parse_pid_file_name("README"), None);
+
+        let home = env::temp_dir().join(format!("ccpclean-pm2-{}", std::process::id()));
+        fs::create_dir_all(home.join("pids")).unwrap();
+        fs::write(home.join("pids").join("api-0.pid"), "4242\n").unwrap();
+        let apps = pm2_apps(&home);
+        let _ = fs::remove_dir_all(&home);
+        assert_eq!(apps.get(&4242), Some(&Pm2App { name: "api".to_string(), id: 0 }));
+    }
+}
@@ -0,0 +1,112 @@
+use std::fmt;
+use std::io;
+use std::process::Command;
+
+/// The systemd unit a process runs in. Only services (which systemd may restart) and
+/// `systemd-run` transient units count; login sessions and terminal or desktop app scopes do
+/// not manage their processes.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Unit {
+    pub name: String,
+    /// Managed by the user's `systemd --user` instance rather than the system manager.
+    pub user: bool,
+}
+
+impl Unit {
+    /// `dev-api.service (systemd --user)`.
+    pub fn display(&self) -> String {
+        format!("{} ({})", self.name, if self.user { "systemd --user" } else { "system" })
+    }
+}
+
+#[derive(Debug)]
+pub enum UnitError {
+    Spawn(io::Error),
+    /// `systemctl stop` exited non-zero, with its stderr.
+    Failed(String),
+}
+
+impl fmt::Display for UnitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnitError::Spawn(e) => write!(f, "Failed to run systemctl: {}", e),
+            UnitError::Failed(stderr) => write!(f, "systemctl stop failed: {}", stderr),
+        }
+    }
+}
+
+fn is_managed(segment: &str) -> bool {
+    if segment.starts_with("user@") {
+        return false;
+    }
+    segment.ends_with(".service") || (segment.starts_with("run-") && segment.ends_with(".scope"))
+}
+
+/// The unit in a `/proc/<pid>/cgroup` file: the innermost managed unit on the unified (v2)
+/// or `name=systemd` (v1) hierarchy.
+fn parse_cgroup(cgroup: &str) -> Option<Unit> {
+    let path = cgroup.lines().find_map(|line| {
+        let mut fields = line.splitn(3, ':');
+        let (_, controllers, path) = (fields.next()?, fields.next()?, fields.next()?);
+        (controllers.is_empty() || controllers == "name=systemd").then_some(path)
+    })?;
+    let name = path.split('/').rev().find(|s| is_managed(s))?;
+    Some(Unit { name: name.to_string(), user: path.contains("/user@") })
+}
+
+#[cfg(target_os = "linux")]
+pub fn unit_of(pid: u32) -> Option<Unit> {
+    parse_cgroup(&std::fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()?)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn unit_of(_pid: u32) -> Option<Unit> {
+    None
+}
+
+/// `systemctl [--user] stop <unit>`. Never prompts for a password, so stopping a system
+/// unit needs root.
+pub fn stop(unit: &Unit) -> Result<(), UnitError> {
+    let mut cmd = Command::new("systemctl");
+    if unit.user {
+        cmd.arg("--user");
+    }
+    let output = cmd
+        .args(["--no-ask-password", "stop", &unit.name])
+        .output()
+        .map_err(UnitError::Spawn)?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(UnitError::Failed(String::from_utf8_lossy(&output.stderr).trim().to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit(name: &str, user: bool) -> Option<Unit> {
+        Some(Unit { name: name.to_string(), user })
+    }
+
+    #[test]
+    fn test_parse_cgroup() {
+        let user = "0::/user.slice/user-1000.slice/user@1000.service/app.slice/dev-api.service";
+        assert_eq!(parse_cgroup(user), unit("dev-api.service", true));
+        let transient = "0::/user.slice/user-1000.slice/user@1000.service/app.slice/run-r3f2a.scope";
+        assert_eq!(parse_cgroup(transient), unit("run-r3f2a.scope", true));
+        assert_eq!(parse_cgroup("0::/system.slice/nginx.service"), unit("nginx.service", false));
+        let v1 = "12:pids:/system.slice/redis.service\n1:name=systemd:/system.slice/redis.service";
+        assert_eq!(parse_cgroup(v1), unit("redis.service", false));
+    }
+
+    #[test]
+    fn test_unmanaged_scopes() {
+        assert_eq!(parse_cgroup("0::/user.slice/user-1000.slice/session-2.scope"), None);
+        let terminal = "0::/user.slice/user-1000.slice/user@1000.service/app.slice/app-org.gnome.Terminal.slice/vte-spawn-1.scope";
+        assert_eq!(parse_cgroup(terminal), None);
+        assert_eq!(parse_cgroup("0::/user.slice/user-1000.slice/user@1000.service/init.scope"), None);
+    }
+}
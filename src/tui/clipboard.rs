@@ -0,0 +1,86 @@
+use std::io::{self, Write};
+use crate::process_info::ProcessInfo;
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = ((b[0] as u32) << 16) | ((b[1] as u32) << 8) | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64[((n >> (18 - 6 * i)) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// The OSC 52 "set clipboard" sequence for `text`. Inside tmux or GNU screen it is wrapped in
+/// a DCS passthrough so it reaches the outer terminal (tmux also needs `allow-passthrough`
+/// or `set-clipboard on`).
+pub fn osc52(text: &str, tmux: bool, screen: bool) -> String {
+    let seq = format!("\x1b]52;c;{}\x07", base64(text.as_bytes()));
+    if tmux {
+        format!("\x1bPtmux;{}\x1b\\", seq.replace('\x1b', "\x1b\x1b"))
+    } else if screen {
+        format!("\x1bP{}\x1b\\", seq)
+    } else {
+        seq
+    }
+}
+
+/// Asks the terminal to put `text` on the system clipboard. Terminals without OSC 52
+/// support ignore the sequence, which is why yanked values are also printed on exit.
+pub fn copy(text: &str) -> io::Result<()> {
+    let tmux = std::env::var_os("TMUX").is_some();
+    let screen = std::env::var_os("STY").is_some();
+    let mut stdout = io::stdout();
+    stdout.write_all(osc52(text, tmux, screen).as_bytes())?;
+    stdout.flush()
+}
+
+/// Fields of a process that can be yanked, as `(label, value)`; empty fields are left out.
+pub fn fields(p: &ProcessInfo) -> Vec<(&'static str, String)> {
+    [
+        ("PID", p.pid.to_string()),
+        ("Ports", p.ports_display()),
+        ("Command", p.cmd.join(" ")),
+        ("Directory", p.cwd.clone().unwrap_or_default()),
+        ("Name", p.name.clone()),
+    ]
+    .into_iter()
+    .filter(|(_, value)| !value.is_empty())
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"node server.js"), "bm9kZSBzZXJ2ZXIuanM=");
+    }
+
+    #[test]
+    fn test_osc52_wrapping() {
+        assert_eq!(osc52("1234", false, false), "\x1b]52;c;MTIzNA==\x07");
+        assert_eq!(osc52("1234", true, false), "\x1bPtmux;\x1b\x1b]52;c;MTIzNA==\x07\x1b\\");
+        assert_eq!(osc52("1234", false, true), "\x1bP\x1b]52;c;MTIzNA==\x07\x1b\\");
+    }
+
+    #[test]
+    fn test_fields_skip_empty() {
+        let p = ProcessInfo { pid: 7, name: "node".to_string(), ..Default::default() };
+        let labels: Vec<&str> = fields(&p).iter().map(|(label, _)| *label).collect();
+        assert_eq!(labels, vec!["PID", "Name"]);
+    }
+}
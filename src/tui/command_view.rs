@@ -0,0 +1,74 @@
+use ratatui::{
+    layout::Rect,
+    style::Modifier,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, Wrap},
+    Frame,
+};
+use crate::process_info::ProcessInfo;
+use super::theme::Theme;
+use super::keys::{self, Action};
+use super::AppState;
+
+/// `cmd` one argument per line, numbered, with the entrypoint (see `ProcessInfo::entrypoint`)
+/// highlighted.
+pub fn argv_lines(p: &ProcessInfo, theme: &Theme) -> Vec<Line<'static>> {
+    let entry = p.entrypoint();
+    p.cmd
+        .iter()
+        .enumerate()
+        .map(|(i, arg)| {
+            let mut spans = vec![Span::styled(format!("{:>3}  ", i), theme.muted)];
+            if Some(i) == entry {
+                spans.push(Span::styled(arg.clone(), theme.accent.add_modifier(Modifier::BOLD)));
+                spans.push(Span::styled("  ← entrypoint", theme.muted));
+            } else {
+                spans.push(Span::raw(arg.clone()));
+            }
+            Line::from(spans)
+        })
+        .collect()
+}
+
+/// Draws `paragraph` (already wrapped) scrolled to `*offset`, clamping the offset to the
+/// content, with a scrollbar when it does not fit.
+pub fn render_scrolled(f: &mut Frame, area: Rect, paragraph: Paragraph, offset: &mut u16) {
+    // The rightmost column is kept for the scrollbar.
+    let text = Rect { width: area.width.saturating_sub(1), ..area };
+    let height = paragraph.line_count(text.width) as u16;
+    let max = height.saturating_sub(area.height);
+    *offset = (*offset).min(max);
+    f.render_widget(paragraph.scroll((*offset, 0)), text);
+    if max > 0 {
+        let mut bar = ScrollbarState::new(max as usize).position(*offset as usize);
+        f.render_stateful_widget(Scrollbar::new(ScrollbarOrientation::VerticalRight), area, &mut bar);
+    }
+}
+
+/// Full-screen view of the current process's command line.
+pub fn render(f: &mut Frame, area: Rect, state: &mut AppState) {
+    let Some(p) = state.current_process() else { return };
+    let heading = state.theme.header;
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled("Directory: ", heading),
+            Span::raw(p.cwd.clone().unwrap_or_else(|| "unknown".to_string())),
+        ]),
+        Line::from(vec![Span::styled("Full:      ", heading), Span::raw(p.cmd.join(" "))]),
+        Line::raw(""),
+        Line::styled(format!("Arguments ({})", p.cmd.len()), heading),
+    ];
+    lines.extend(argv_lines(p, &state.theme));
+    let scroll = [Action::MoveUp, Action::MoveDown, Action::ScrollUp, Action::ScrollDown];
+    let hints = keys::title_hints(&state.keymap, &[(&scroll, "scroll"), (&[Action::Quit], "close")]);
+    let title = format!(" Command of {} (PID {})  {} ", p.name, p.pid, hints);
+
+    let block = Block::default().borders(Borders::ALL).title(title);
+    let inner = block.inner(area);
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+    let paragraph = Paragraph::new(lines).wrap(Wrap { trim: false });
+    if let Some(ref mut offset) = state.command_inspector {
+        render_scrolled(f, inner, paragraph, offset);
+    }
+}
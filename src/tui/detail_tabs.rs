@@ -0,0 +1,167 @@
+use std::net::SocketAddr;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use crate::inspect::{self, Files};
+use crate::process_info::ProcessInfo;
+use crate::scanner;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DetailTab {
+    Overview,
+    Environment,
+    Files,
+    Connections,
+    Children,
+}
+
+impl DetailTab {
+    pub const ALL: [DetailTab; 5] = [
+        DetailTab::Overview,
+        DetailTab::Environment,
+        DetailTab::Files,
+        DetailTab::Connections,
+        DetailTab::Children,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            DetailTab::Overview => "Overview",
+            DetailTab::Environment => "Environment",
+            DetailTab::Files => "Files",
+            DetailTab::Connections => "Connections",
+            DetailTab::Children => "Children",
+        }
+    }
+
+    /// The tab `delta` places away, wrapping around.
+    pub fn cycle(self, delta: isize) -> DetailTab {
+        let len = Self::ALL.len() as isize;
+        let i = Self::ALL.iter().position(|&t| t == self).unwrap_or(0) as isize;
+        Self::ALL[(i + delta).rem_euclid(len) as usize]
+    }
+}
+
+/// One part of an `Inspection`, read on a background thread.
+#[derive(Debug)]
+enum Loaded {
+    Env(Vec<(String, String)>),
+    Files(Files),
+    Peers(Vec<(u16, SocketAddr)>),
+}
+
+/// Data behind the Environment, Files and Connections tabs for one PID. Each part is read in
+/// the background when its tab is shown (lsof on macOS can take a while), re-read after every
+/// sampling tick, and dropped when another process is selected. Stale data stays on screen
+/// until the fresh read arrives.
+#[derive(Debug, Default)]
+pub struct Inspection {
+    pub pid: u32,
+    pub env: Option<Vec<(String, String)>>,
+    pub files: Option<Files>,
+    pub peers: Option<Vec<(u16, SocketAddr)>>,
+    /// Tabs whose data was read since the last `invalidate`.
+    fresh: Vec<DetailTab>,
+    /// Reads in flight, at most one per tab.
+    pending: Vec<(DetailTab, Receiver<Loaded>)>,
+}
+
+impl Inspection {
+    pub fn new(pid: u32) -> Self {
+        Self { pid, ..Default::default() }
+    }
+
+    /// Marks everything for re-reading the next time its tab is shown.
+    pub fn invalidate(&mut self) {
+        self.fresh.clear();
+    }
+
+    /// Applies finished reads and starts one for `tab` if its data is missing or stale.
+    pub fn load(&mut self, tab: DetailTab, p: &ProcessInfo) {
+        self.poll();
+        if tab == DetailTab::Overview
+            || tab == DetailTab::Children
+            || self.fresh.contains(&tab)
+            || self.pending.iter().any(|(t, _)| *t == tab)
+        {
+            return;
+        }
+        let (tx, rx) = mpsc::channel();
+        let (pid, ports) = (p.pid, p.ports.clone());
+        thread::spawn(move || {
+            let loaded = match tab {
+                DetailTab::Environment => Loaded::Env(inspect::environment(pid)),
+                DetailTab::Files => Loaded::Files(inspect::files(pid)),
+                _ => Loaded::Peers(scanner::established_peers(&ports)),
+            };
+            let _ = tx.send(loaded);
+        });
+        self.pending.push((tab, rx));
+    }
+
+    fn poll(&mut self) {
+        let mut finished = Vec::new();
+        self.pending.retain(|(tab, rx)| match rx.try_recv() {
+            Ok(loaded) => {
+                finished.push((*tab, loaded));
+                false
+            }
+            Err(mpsc::TryRecvError::Empty) => true,
+            Err(mpsc::TryRecvError::Disconnected) => false,
+        });
+        for (tab, loaded) in finished {
+            match loaded {
+                Loaded::Env(env) => self.env = Some(env),
+                Loaded::Files(files) => self.files = Some(files),
+                Loaded::Peers(peers) => self.peers = Some(peers),
+            }
+            self.fresh.push(tab);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cycle_wraps() {
+        assert_eq!(DetailTab::Overview.cycle(1), DetailTab::Environment);
+        assert_eq!(DetailTab::Overview.cycle(-1), DetailTab::Children);
+        assert_eq!(DetailTab::Children.cycle(1), DetailTab::Overview);
+    }
+
+    /// Loads `tab` until its background read has landed.
+    fn load_until_fresh(inspection: &mut Inspection, tab: DetailTab, p: &ProcessInfo) {
+        for _ in 0..200 {
+            inspection.load(tab, p);
+            if inspection.fresh.contains(&tab) {
+                return;
+            }
+            thread::sleep(std::time::Duration::from_millis(10));
+        }
+        panic!("{:?} never loaded", tab);
+    }
+
+    #[test]
+    fn test_load_only_reads_the_tab() {
+        let p = ProcessInfo { pid: std::process::id(), ..Default::default() };
+        let mut inspection = Inspection::new(p.pid);
+        load_until_fresh(&mut inspection, DetailTab::Connections, &p);
+        assert_eq!(inspection.peers, Some(Vec::new()));
+        assert!(inspection.env.is_none());
+        assert!(inspection.files.is_none());
+    }
+
+    #[test]
+    fn test_invalidate_rereads_and_keeps_stale_data() {
+        let p = ProcessInfo { pid: std::process::id(), ..Default::default() };
+        let mut inspection = Inspection::new(p.pid);
+        load_until_fresh(&mut inspection, DetailTab::Files, &p);
+        inspection.invalidate();
+        assert!(inspection.files.is_some());
+        inspection.load(DetailTab::Files, &p);
+        assert_eq!(inspection.pending.len(), 1);
+        load_until_fresh(&mut inspection, DetailTab::Files, &p);
+        assert!(inspection.pending.is_empty());
+    }
+}
@@ -1,13 +1,18 @@
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Sparkline, Wrap},
     Frame,
 };
-use crate::filter::{score_display, FilterMode};
-use super::AppState;
+use crate::container;
+use crate::filter::{score_display, FilterMode, ScoreLevel};
+use crate::process_info::memory_display;
+use super::detail_tabs::DetailTab;
+use super::metrics::{self, Sample};
+use super::keys::{self, Action};
+use super::{command_view, mouse, AppState};
 
-pub fn render(f: &mut Frame, area: Rect, state: &AppState) {
+pub fn render(f: &mut Frame, area: Rect, state: &mut AppState) {
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([Constraint::Length(26), Constraint::Min(40)])
@@ -17,12 +22,12 @@ pub fn render(f: &mut Frame, area: Rect, state: &AppState) {
     render_detail_panel(f, chunks[1], state);
 }
 
-fn render_process_list(f: &mut Frame, area: Rect, state: &AppState) {
+fn render_process_list(f: &mut Frame, area: Rect, state: &mut AppState) {
     let items: Vec<ListItem> = state
         .processes
         .iter()
         .map(|p| {
-            let label = format!("{:<10} :{}", p.name, p.ports_display());
+            let label = format!("{:<10} :{}", p.name_display(), p.ports_display());
             ListItem::new(label)
         })
         .collect();
@@ -32,39 +37,262 @@ fn render_process_list(f: &mut Frame, area: Rect, state: &AppState) {
         FilterMode::Loose => "Loose: all",
     };
 
+    let hints = keys::title_hints(&state.keymap, &[(&[Action::SwitchView], "tree")]);
     let list = List::new(items)
-        .block(Block::default().borders(Borders::ALL).title(format!(" [{}] Tab=list ", mode_str)))
-        .highlight_style(Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD))
+        .block(Block::default().borders(Borders::ALL).title(format!(" [{}] {} ", mode_str, hints)))
+        .highlight_style(state.theme.selected)
         .highlight_symbol("▶ ");
 
     let mut list_state = ListState::default().with_selected(Some(state.selected_index));
     f.render_stateful_widget(list, area, &mut list_state);
+    let inner = Block::default().borders(Borders::ALL).inner(area);
+    state.hits.rows = Some((inner, list_state.offset()));
+    state.hits.row_count = state.processes.len();
 }
 
-fn render_detail_panel(f: &mut Frame, area: Rect, state: &AppState) {
-    let content = match state.current_process() {
-        None => "No process selected".to_string(),
+fn render_detail_panel(f: &mut Frame, area: Rect, state: &mut AppState) {
+    let block = Block::default().borders(Borders::ALL).title(" Process Detail ");
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Min(1), Constraint::Length(1)])
+        .split(inner);
+
+    render_tabs(f, chunks[0], state);
+    match state.detail_tab {
+        DetailTab::Overview => render_overview(f, chunks[1], state),
+        tab => {
+            let lines = tab_lines(tab, state);
+            render_body(f, chunks[1], lines, state);
+        }
+    }
+
+    let (actions, buttons) = mouse::action_bar(chunks[2], &keys::footer(state));
+    state.hits.buttons = buttons;
+    f.render_widget(Paragraph::new(actions).style(state.theme.muted), chunks[2]);
+}
+
+/// One line of tab titles, each recorded as a click target.
+fn render_tabs(f: &mut Frame, area: Rect, state: &mut AppState) {
+    let mut spans = Vec::new();
+    let mut x = area.x;
+    for (i, tab) in DetailTab::ALL.into_iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::styled(" │ ", state.theme.muted));
+            x += 3;
+        }
+        let title = format!(" {} ", tab.title());
+        let width = (title.chars().count() as u16).min(area.right().saturating_sub(x));
+        state.hits.tabs.push((Rect::new(x, area.y, width, 1), tab));
+        x += width;
+        let style = if tab == state.detail_tab { state.theme.selected } else { state.theme.header };
+        spans.push(Span::styled(title, style));
+    }
+    f.render_widget(Paragraph::new(Line::from(spans)), area);
+}
+
+fn render_overview(f: &mut Frame, area: Rect, state: &mut AppState) {
+    let content: Vec<Line<'static>> = match state.current_process() {
+        None => vec![Line::raw("No process selected")],
         Some(p) => {
             let parent_str = match (&p.parent_name, p.parent_pid) {
                 (Some(name), Some(pid)) => format!("{} (PID {})", name, pid),
                 _ => "unknown".to_string(),
             };
-            format!(
-                "PID:        {}\nName:       {}\nPorts:      {}\nCommand:    {}\nStarted:    {}\nMemory:     {}\nParent:     {}\nConfidence: {} {}\n\n[Enter] Kill   [Q] Quit",
-                p.pid,
-                p.name,
-                p.ports_display(),
-                p.cmd.join(" "),
-                p.uptime_display(),
-                p.memory_display(),
-                parent_str,
-                score_display(p.score),
-                if p.score >= 70 { "High" } else if p.score >= 40 { "Medium" } else { "Low" },
-            )
+            let mut lines: Vec<Line<'static>> = [
+                ("PID", p.pid.to_string()),
+                ("Name", p.name.clone()),
+                ("Ports", p.ports_display()),
+                ("Started", p.uptime_display()),
+                ("Memory", p.memory_display()),
+                ("Activity", p.idle_display()),
+                ("Parent", parent_str),
+            ]
+            .into_iter()
+            .map(|(label, value)| Line::raw(format!("{:<12}{}", format!("{}:", label), value)))
+            .collect();
+            lines.push(Line::from(vec![
+                Span::raw(format!("{:<12}", "Confidence:")),
+                Span::styled(
+                    format!("{} {}", score_display(p.score), ScoreLevel::of(p.score).label()),
+                    state.theme.score(p.score),
+                ),
+            ]));
+            if state.protect.is_protected(p) {
+                lines.push(Line::styled("Protected:  matches the [protect] config section", state.theme.protected));
+            } else if p.is_system() {
+                lines.push(Line::styled("System:     owned by a system account", state.theme.system));
+            }
+            if p.stopped {
+                lines.push(Line::styled("Stopped:    paused; send SIGCONT to resume", state.theme.muted));
+            }
+            if let Some(ref c) = p.container {
+                let action = if c.can_stop() { "Enter stops the container" } else { "no API socket, Enter kills the process" };
+                lines.push(Line::raw(format!("{:<12}{} via {}; {}", "Container:", c.display(), c.runtime.name(), action)));
+            } else if let Some(ref unit) = p.unit {
+                lines.push(Line::raw(format!("{:<12}{}; Enter stops the unit", "Unit:", unit.display())));
+            }
+            if let Some(ref s) = p.supervisor {
+                let action = if s.pm2_app.is_some() { "Enter runs pm2 delete" } else { "Enter kills the supervisor" };
+                lines.push(Line::styled(
+                    format!("{:<12}{}; will respawn if killed, {}", "Supervisor:", s.display(), action),
+                    state.theme.accent,
+                ));
+            }
+            if let Some(ref pane) = p.pane {
+                lines.push(Line::raw(format!("{:<12}{}; T to jump to or close it", "Terminal:", pane.display())));
+            }
+            if p.container.is_none() && container::is_runtime(&p.name) {
+                lines.push(Line::styled("Runtime:    container runtime; killing it stops its containers", state.theme.system));
+            }
+            lines.push(Line::raw("Command:"));
+            lines.extend(command_view::argv_lines(p, &state.theme));
+            lines
         }
     };
 
-    let paragraph = Paragraph::new(content)
-        .block(Block::default().borders(Borders::ALL).title(" Process Detail "));
-    f.render_widget(paragraph, area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(9)])
+        .split(area);
+    render_resources(f, chunks[1], state);
+    render_body(f, chunks[0], content, state);
+}
+
+/// Wrapped, scrollable text of the current tab.
+fn render_body(f: &mut Frame, area: Rect, lines: Vec<Line<'static>>, state: &mut AppState) {
+    state.hits.scrollable = Some(area);
+    let paragraph = Paragraph::new(lines).wrap(Wrap { trim: false });
+    command_view::render_scrolled(f, area, paragraph, &mut state.detail_scroll);
+}
+
+/// Lines of the Environment, Files, Connections and Children tabs.
+fn tab_lines(tab: DetailTab, state: &AppState) -> Vec<Line<'static>> {
+    let Some(p) = state.current_process() else {
+        return vec![Line::raw("No process selected")];
+    };
+    let inspection = state.inspection.as_ref().filter(|i| i.pid == p.pid);
+    let muted = state.theme.muted;
+    let heading = state.theme.header;
+    match tab {
+        DetailTab::Overview => Vec::new(),
+        DetailTab::Environment => match inspection.and_then(|i| i.env.as_ref()) {
+            None => vec![Line::styled("Loading...", muted)],
+            Some(env) if env.is_empty() => vec![Line::styled(
+                "No environment available (the process may belong to another user)",
+                muted,
+            )],
+            Some(env) => env
+                .iter()
+                .map(|(name, value)| {
+                    Line::from(vec![
+                        Span::styled(name.clone(), state.theme.accent),
+                        Span::raw(format!("={}", value)),
+                    ])
+                })
+                .collect(),
+        },
+        DetailTab::Files => match inspection.and_then(|i| i.files.as_ref()) {
+            None => vec![Line::styled("Loading...", muted)],
+            Some(files) => {
+                let unknown = || "unknown".to_string();
+                let mut lines = vec![
+                    Line::raw(format!("{:<12}{}", "Executable:", files.exe.clone().unwrap_or_else(unknown))),
+                    Line::raw(format!("{:<12}{}", "Directory:", files.cwd.clone().unwrap_or_else(unknown))),
+                    Line::raw(""),
+                ];
+                match files.open {
+                    None => lines.push(Line::styled("Open files are not available on this platform", muted)),
+                    Some(ref open) => {
+                        lines.push(Line::styled(format!("Open files ({})", open.len()), heading));
+                        lines.extend(open.iter().map(|(fd, target)| Line::raw(format!("{:>5}  {}", fd, target))));
+                    }
+                }
+                lines
+            }
+        },
+        DetailTab::Connections => match inspection.and_then(|i| i.peers.as_ref()) {
+            None => vec![Line::styled("Loading...", muted)],
+            Some(_) if p.ports.is_empty() => vec![Line::styled("Not listening on any port", muted)],
+            Some(peers) => {
+                let mut lines = Vec::new();
+                for port in &p.ports {
+                    let on_port: Vec<_> = peers.iter().filter(|(local, _)| local == port).collect();
+                    lines.push(Line::styled(format!("Port {}: {} established", port, on_port.len()), heading));
+                    lines.extend(on_port.iter().map(|(_, peer)| Line::raw(format!("  ← {}", peer))));
+                }
+                lines
+            }
+        },
+        DetailTab::Children => {
+            let children: Vec<Line> = state
+                .all_processes
+                .iter()
+                .filter(|c| c.parent_pid == Some(p.pid))
+                .map(|c| {
+                    let ports = if c.ports.is_empty() { String::new() } else { format!(" :{}", c.ports_display()) };
+                    Line::raw(format!("{:>7}  {}{}  {}", c.pid, c.name_display(), ports, c.cmd.join(" ")))
+                })
+                .collect();
+            if children.is_empty() {
+                vec![Line::styled("No child processes", muted)]
+            } else {
+                children
+            }
+        }
+    }
+}
+
+/// CPU, memory and connection sparklines over the samples taken since the TUI started.
+fn render_resources(f: &mut Frame, area: Rect, state: &AppState) {
+    let history = state.current_process().and_then(|p| state.metrics.get(p.pid));
+    let Some(history) = history.filter(|h| !h.is_empty()) else {
+        f.render_widget(Paragraph::new("Collecting resource samples...").style(state.theme.muted), area);
+        return;
+    };
+    let samples: Vec<&Sample> = history.iter().collect();
+    let last = samples[samples.len() - 1];
+    let first = samples[0];
+    let span = format!(
+        "last {}s",
+        (samples.len() - 1) as u64 * metrics::SAMPLE_INTERVAL.as_secs()
+    );
+
+    let peak_cpu = samples.iter().map(|s| s.cpu_percent).fold(0.0, f32::max);
+    let growth = last.memory_kb as i64 - first.memory_kb as i64;
+    let growth = if growth >= 0 {
+        format!("+{}", memory_display(growth as u64))
+    } else {
+        format!("-{}", memory_display(growth.unsigned_abs()))
+    };
+    let peak_conns = samples.iter().map(|s| s.connections).max().unwrap_or(0);
+
+    let charts: [(String, Vec<u64>); 3] = [
+        (
+            format!("CPU {:.1}%  (peak {:.1}%, {})", last.cpu_percent, peak_cpu, span),
+            // Tenths of a percent, so low but nonzero usage still shows.
+            samples.iter().map(|s| (s.cpu_percent * 10.0) as u64).collect(),
+        ),
+        (
+            format!("RSS {}  ({} over {})", memory_display(last.memory_kb), growth, span),
+            samples.iter().map(|s| s.memory_kb).collect(),
+        ),
+        (
+            format!("Connections {}  (peak {})", last.connections, peak_conns),
+            samples.iter().map(|s| s.connections as u64).collect(),
+        ),
+    ];
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Length(2)].repeat(3))
+        .split(area);
+    for (i, (label, data)) in charts.iter().enumerate() {
+        f.render_widget(Paragraph::new(label.as_str()).style(state.theme.header), rows[i * 2]);
+        // Show the most recent samples that fit.
+        let width = rows[i * 2 + 1].width as usize;
+        let data = &data[data.len().saturating_sub(width)..];
+        f.render_widget(Sparkline::default().data(data).style(state.theme.accent), rows[i * 2 + 1]);
+    }
 }
@@ -0,0 +1,158 @@
+use std::collections::{HashMap, HashSet};
+use crate::process_info::ProcessInfo;
+
+/// Listed processes that belong to one project.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Group {
+    /// Stable identity used for collapsing: the project root, or `pid:<root>` for the
+    /// process-tree fallback.
+    pub key: String,
+    pub label: String,
+    pub pids: Vec<u32>,
+    pub ports: Vec<u16>,
+    pub memory_kb: u64,
+    pub expanded: bool,
+}
+
+/// One visible line of the grouped list view.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GroupRow {
+    Header(Group),
+    Process(u32),
+}
+
+/// Groups `listed` by project root. Processes without a known working directory fall back
+/// to the root of their process tree (the topmost ancestor below PID 1 in `all`). Groups keep
+/// the order of their first member, so the highest-scored project comes first; members of
+/// `collapsed` groups are hidden.
+pub fn build_rows(
+    all: &[ProcessInfo],
+    listed: &[ProcessInfo],
+    collapsed: &HashSet<String>,
+) -> Vec<GroupRow> {
+    let by_pid: HashMap<u32, &ProcessInfo> = all.iter().map(|p| (p.pid, p)).collect();
+
+    let mut groups: Vec<Group> = Vec::new();
+    for p in listed {
+        let (key, label) = group_key(p, &by_pid);
+        let idx = match groups.iter().position(|g| g.key == key) {
+            Some(idx) => idx,
+            None => {
+                groups.push(Group {
+                    expanded: !collapsed.contains(&key),
+                    key,
+                    label,
+                    pids: Vec::new(),
+                    ports: Vec::new(),
+                    memory_kb: 0,
+                });
+                groups.len() - 1
+            }
+        };
+        let group = &mut groups[idx];
+        group.pids.push(p.pid);
+        group.memory_kb += p.memory_kb;
+        for port in &p.ports {
+            if !group.ports.contains(port) {
+                group.ports.push(*port);
+            }
+        }
+    }
+
+    let mut rows = Vec::new();
+    for mut group in groups {
+        group.ports.sort_unstable();
+        let members = if group.expanded { group.pids.clone() } else { Vec::new() };
+        rows.push(GroupRow::Header(group));
+        rows.extend(members.into_iter().map(GroupRow::Process));
+    }
+    rows
+}
+
+fn group_key(p: &ProcessInfo, by_pid: &HashMap<u32, &ProcessInfo>) -> (String, String) {
+    if let Some(ref root) = p.project_root {
+        return (root.clone(), root.clone());
+    }
+    let mut root = p;
+    let mut seen = HashSet::new();
+    while let Some(parent) = root
+        .parent_pid
+        .filter(|&ppid| ppid > 1 && seen.insert(ppid))
+        .and_then(|ppid| by_pid.get(&ppid))
+    {
+        root = parent;
+    }
+    (
+        format!("pid:{}", root.pid),
+        format!("process tree of {} (PID {})", root.name, root.pid),
+    )
+}
+
+/// PIDs covered by the row at `index`: every member for a header, or the process itself.
+pub fn row_pids(rows: &[GroupRow], index: usize) -> Vec<u32> {
+    match rows.get(index) {
+        Some(GroupRow::Header(g)) => g.pids.clone(),
+        Some(GroupRow::Process(pid)) => vec![*pid],
+        None => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_proc(pid: u32, parent: Option<u32>, root: Option<&str>, ports: Vec<u16>) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            name: format!("proc{}", pid),
+            project_root: root.map(|r| r.to_string()),
+            ports,
+            memory_kb: 100,
+            parent_pid: parent,
+            ..Default::default()
+        }
+    }
+
+    fn sample() -> Vec<ProcessInfo> {
+        vec![
+            make_proc(10, Some(1), Some("/src/shop"), vec![3000]),
+            make_proc(20, Some(1), None, vec![]),
+            make_proc(21, Some(20), None, vec![9000]),
+            make_proc(11, Some(1), Some("/src/shop"), vec![3001, 3000]),
+            make_proc(12, Some(1), Some("/src/blog"), vec![4000]),
+        ]
+    }
+
+    fn listed(all: &[ProcessInfo]) -> Vec<ProcessInfo> {
+        all.iter().filter(|p| !p.ports.is_empty()).cloned().collect()
+    }
+
+    #[test]
+    fn test_build_rows_groups_by_project() {
+        let all = sample();
+        let rows = build_rows(&all, &listed(&all), &HashSet::new());
+        assert_eq!(rows.len(), 7);
+        let GroupRow::Header(ref shop) = rows[0] else { panic!("expected header") };
+        assert_eq!(shop.key, "/src/shop");
+        assert_eq!(shop.pids, vec![10, 11]);
+        assert_eq!(shop.ports, vec![3000, 3001]);
+        assert_eq!(shop.memory_kb, 200);
+        assert_eq!(rows[1], GroupRow::Process(10));
+        assert_eq!(rows[2], GroupRow::Process(11));
+        let GroupRow::Header(ref tree) = rows[3] else { panic!("expected header") };
+        assert_eq!(tree.key, "pid:20");
+        assert_eq!(tree.label, "process tree of proc20 (PID 20)");
+    }
+
+    #[test]
+    fn test_build_rows_collapsed_and_row_pids() {
+        let all = sample();
+        let collapsed: HashSet<String> = ["/src/shop".to_string()].into_iter().collect();
+        let rows = build_rows(&all, &listed(&all), &collapsed);
+        assert_eq!(rows.len(), 5);
+        assert!(matches!(rows[0], GroupRow::Header(ref g) if !g.expanded));
+        assert_eq!(row_pids(&rows, 0), vec![10, 11]);
+        assert_eq!(row_pids(&rows, 2), vec![21]);
+        assert!(row_pids(&rows, 9).is_empty());
+    }
+}
@@ -0,0 +1,47 @@
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+use crate::filter::FilterMode;
+use super::keys::{self, Action};
+use super::AppState;
+
+/// Keybinding overlay, generated from `keys::BINDINGS`.
+pub fn render(f: &mut Frame, area: Rect, state: &AppState) {
+    let mode_str = match state.filter_mode {
+        FilterMode::Strict => "Strict: dev runtimes only",
+        FilterMode::Loose => "Loose: all listening processes",
+    };
+    let heading = state.theme.header;
+
+    let mut lines = vec![
+        Line::from(vec![Span::styled("Filter mode: ", heading), Span::raw(mode_str)]),
+    ];
+    for (title, bindings) in keys::sections() {
+        lines.push(Line::raw(""));
+        lines.push(Line::styled(title, heading));
+        for b in bindings {
+            let keys = keys::keys_display(state.keymap.keys(b.action));
+            let mut spans = vec![
+                Span::styled(format!("  {:<16}", keys), state.theme.accent),
+                Span::raw(b.description),
+            ];
+            if state.keymap.is_overridden(b.action) {
+                spans.push(Span::styled("  (custom)", state.theme.group));
+            }
+            lines.push(Line::from(spans));
+        }
+    }
+
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let [popup] = Layout::horizontal([Constraint::Percentage(80)]).flex(Flex::Center).areas(area);
+    let [popup] = Layout::vertical([Constraint::Length(height)]).flex(Flex::Center).areas(popup);
+
+    let paragraph = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .block(Block::default().borders(Borders::ALL).title(format!(" Keybindings  {} ", keys::title_hints(&state.keymap, &[(&[Action::Quit], "close")]))));
+    f.render_widget(Clear, popup);
+    f.render_widget(paragraph, popup);
+}
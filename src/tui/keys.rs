@@ -0,0 +1,577 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use crossterm::event::KeyCode;
+use super::{AppState, View};
+
+/// Everything a key (or a click on a footer entry) can do.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    ToggleCheck,
+    SelectAll,
+    SelectSubtree,
+    KillSelected,
+    Signal,
+    Undo,
+    Collapse,
+    Expand,
+    ToggleGroup,
+    NextTab,
+    PrevTab,
+    ScrollUp,
+    ScrollDown,
+    InspectCommand,
+    Yank,
+    Pane,
+    SwitchFilter,
+    SwitchView,
+    Help,
+    Quit,
+}
+
+impl Action {
+    /// Name used in the `[keys]` table of the config file.
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::MoveUp => "move_up",
+            Action::MoveDown => "move_down",
+            Action::ToggleCheck => "toggle_check",
+            Action::SelectAll => "select_all",
+            Action::SelectSubtree => "select_subtree",
+            Action::KillSelected => "kill_selected",
+            Action::Signal => "signal",
+            Action::Undo => "undo",
+            Action::Collapse => "collapse",
+            Action::Expand => "expand",
+            Action::ToggleGroup => "toggle_group",
+            Action::NextTab => "next_tab",
+            Action::PrevTab => "prev_tab",
+            Action::ScrollUp => "scroll_up",
+            Action::ScrollDown => "scroll_down",
+            Action::InspectCommand => "inspect_command",
+            Action::Yank => "yank",
+            Action::Pane => "pane",
+            Action::SwitchFilter => "switch_filter",
+            Action::SwitchView => "switch_view",
+            Action::Help => "help",
+            Action::Quit => "quit",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Action> {
+        BINDINGS.iter().map(|b| b.action).find(|a| a.name() == name)
+    }
+
+    /// Whether the action does anything in the current state, beyond being bound in the view.
+    pub fn available(self, state: &AppState) -> bool {
+        match self {
+            Action::Collapse | Action::Expand => state.can_fold(),
+            Action::Pane => state.current_process().is_some_and(|p| p.pane.is_some()),
+            _ => true,
+        }
+    }
+}
+
+pub struct Binding {
+    pub action: Action,
+    pub keys: &'static [KeyCode],
+    /// Short footer label; `None` leaves the binding to the help overlay.
+    pub footer: Option<&'static str>,
+    pub description: &'static str,
+    pub views: &'static [View],
+}
+
+const ALL: &[View] = &[View::List, View::Detail, View::Tree, View::Ports];
+
+/// The one keybinding table with the default keys: the runner dispatches from it (through
+/// `Keymap`), and the footers, the help overlay and the README keybinding table are generated
+/// from / checked against it.
+pub const BINDINGS: &[Binding] = &[
+    Binding {
+        action: Action::MoveUp,
+        keys: &[KeyCode::Up, KeyCode::Char('k')],
+        footer: None,
+        description: "Move up",
+        views: ALL,
+    },
+    Binding {
+        action: Action::MoveDown,
+        keys: &[KeyCode::Down, KeyCode::Char('j')],
+        footer: None,
+        description: "Move down",
+        views: ALL,
+    },
+    Binding {
+        action: Action::ToggleCheck,
+        keys: &[KeyCode::Char(' ')],
+        footer: Some("select"),
+        description: "Select / deselect the current row (a whole project or every owner of a port)",
+        views: ALL,
+    },
+    Binding {
+        action: Action::SelectAll,
+        keys: &[KeyCode::Char('a'), KeyCode::Char('A')],
+        footer: Some("all"),
+        description: "Select / deselect all",
+        views: &[View::List, View::Detail],
+    },
+    Binding {
+        action: Action::SelectSubtree,
+        keys: &[KeyCode::Char('s'), KeyCode::Char('S')],
+        footer: Some("select subtree"),
+        description: "Select / deselect every listener in the selected subtree",
+        views: &[View::Tree],
+    },
+    Binding {
+        action: Action::KillSelected,
+        keys: &[KeyCode::Enter],
+        footer: Some("kill"),
+        description: "Kill selected processes, or the current row when none are selected (in the list view, only project headers)",
+        views: ALL,
+    },
+    Binding {
+        action: Action::Signal,
+        keys: &[KeyCode::Char('z'), KeyCode::Char('Z')],
+        footer: Some("signal"),
+        description: "Send another signal (stop, continue, hang up, interrupt...) to the same processes as kill",
+        views: ALL,
+    },
+    Binding {
+        action: Action::Undo,
+        keys: &[KeyCode::Char('u'), KeyCode::Char('U')],
+        footer: Some("undo kill"),
+        description: "Undo the last kill: restart the killed processes",
+        views: ALL,
+    },
+    Binding {
+        action: Action::Collapse,
+        keys: &[KeyCode::Left, KeyCode::Char('h')],
+        footer: Some("collapse"),
+        description: "Collapse the selected tree node or project group",
+        views: &[View::List, View::Tree],
+    },
+    Binding {
+        action: Action::Expand,
+        keys: &[KeyCode::Right, KeyCode::Char('l')],
+        footer: Some("expand"),
+        description: "Expand the selected tree node or project group",
+        views: &[View::List, View::Tree],
+    },
+    Binding {
+        action: Action::ToggleGroup,
+        keys: &[KeyCode::Char('g'), KeyCode::Char('G')],
+        footer: Some("group"),
+        description: "Group the list by project / ungroup",
+        views: &[View::List],
+    },
+    Binding {
+        action: Action::NextTab,
+        keys: &[KeyCode::Right, KeyCode::Char('l'), KeyCode::Char(']')],
+        footer: Some("next tab"),
+        description: "Next detail tab: Overview, Environment, Files, Connections, Children",
+        views: &[View::Detail],
+    },
+    Binding {
+        action: Action::PrevTab,
+        keys: &[KeyCode::Left, KeyCode::Char('h'), KeyCode::Char('[')],
+        footer: None,
+        description: "Previous detail tab",
+        views: &[View::Detail],
+    },
+    Binding {
+        action: Action::ScrollUp,
+        keys: &[KeyCode::PageUp, KeyCode::Char('K')],
+        footer: None,
+        description: "Scroll the detail panel up",
+        views: &[View::Detail],
+    },
+    Binding {
+        action: Action::ScrollDown,
+        keys: &[KeyCode::PageDown, KeyCode::Char('J')],
+        footer: None,
+        description: "Scroll the detail panel down",
+        views: &[View::Detail],
+    },
+    Binding {
+        action: Action::InspectCommand,
+        keys: &[KeyCode::Char('c'), KeyCode::Char('C')],
+        footer: Some("command"),
+        description: "Show the full command line of the current process, one argument per line",
+        views: ALL,
+    },
+    Binding {
+        action: Action::Yank,
+        keys: &[KeyCode::Char('y'), KeyCode::Char('Y')],
+        footer: Some("copy"),
+        description: "Copy the PID, ports, command, directory or name of the current process to the clipboard",
+        views: ALL,
+    },
+    Binding {
+        action: Action::Pane,
+        keys: &[KeyCode::Char('t'), KeyCode::Char('T')],
+        footer: Some("pane"),
+        description: "Jump to or close the tmux pane / screen window the current process runs in",
+        views: ALL,
+    },
+    Binding {
+        action: Action::SwitchFilter,
+        keys: &[KeyCode::Char('f'), KeyCode::Char('F')],
+        footer: Some("filter"),
+        description: "Switch filter: Strict (dev runtimes only) / Loose (all listening processes)",
+        views: ALL,
+    },
+    Binding {
+        action: Action::SwitchView,
+        keys: &[KeyCode::Tab],
+        footer: Some("next view"),
+        description: "Cycle list view → detail view → tree view → port view",
+        views: ALL,
+    },
+    Binding {
+        action: Action::Help,
+        keys: &[KeyCode::Char('?')],
+        footer: Some("help"),
+        description: "Show / hide this help",
+        views: ALL,
+    },
+    Binding {
+        action: Action::Quit,
+        keys: &[KeyCode::Char('q'), KeyCode::Char('Q'), KeyCode::Esc],
+        footer: Some("quit"),
+        description: "Quit",
+        views: ALL,
+    },
+];
+
+#[derive(Debug, PartialEq)]
+pub enum KeymapError {
+    UnknownAction(String),
+    UnknownKey { action: String, key: String },
+    Conflict { key: String, first: Action, second: Action },
+}
+
+impl std::fmt::Display for KeymapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeymapError::UnknownAction(name) => {
+                let names: Vec<&str> = BINDINGS.iter().map(|b| b.action.name()).collect();
+                write!(f, "unknown action `{}` in [keys] (expected one of: {})", name, names.join(", "))
+            }
+            KeymapError::UnknownKey { action, key } => write!(
+                f,
+                "unknown key `{}` for `{}` (use a single character, Space, Enter, Tab, Esc, Backspace, Delete, Up, Down, Left, Right, Home, End, PageUp, PageDown or F1-F12)",
+                key, action
+            ),
+            KeymapError::Conflict { key, first, second } => write!(
+                f,
+                "key `{}` is bound to both `{}` and `{}`; rebind one of them in [keys]",
+                key,
+                first.name(),
+                second.name()
+            ),
+        }
+    }
+}
+
+/// Parses a key as written in the config file: a single character (case-sensitive) or a key
+/// name (case-insensitive).
+pub fn parse_key(s: &str) -> Option<KeyCode> {
+    let mut chars = s.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+    let code = match s.to_ascii_lowercase().as_str() {
+        "space" => KeyCode::Char(' '),
+        "enter" | "return" => KeyCode::Enter,
+        "tab" => KeyCode::Tab,
+        "esc" | "escape" => KeyCode::Esc,
+        "backspace" => KeyCode::Backspace,
+        "delete" | "del" => KeyCode::Delete,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        other => {
+            let n: u8 = other.strip_prefix('f')?.parse().ok()?;
+            if !(1..=12).contains(&n) {
+                return None;
+            }
+            KeyCode::F(n)
+        }
+    };
+    Some(code)
+}
+
+/// Keys of every action: the defaults from `BINDINGS`, with the actions listed in the config
+/// file's `[keys]` table replaced.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    keys: HashMap<Action, Vec<KeyCode>>,
+    overridden: HashSet<Action>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            keys: BINDINGS.iter().map(|b| (b.action, b.keys.to_vec())).collect(),
+            overridden: HashSet::new(),
+        }
+    }
+}
+
+impl Keymap {
+    /// Applies `[keys]` overrides such as `kill_selected = ["Enter", "x"]`, rejecting unknown
+    /// actions or keys and any key bound to two actions that share a view.
+    pub fn from_config(table: &BTreeMap<String, Vec<String>>) -> Result<Self, KeymapError> {
+        let mut map = Self::default();
+        for (name, keys) in table {
+            let action = Action::from_name(name).ok_or_else(|| KeymapError::UnknownAction(name.clone()))?;
+            let codes = keys
+                .iter()
+                .map(|k| {
+                    parse_key(k).ok_or_else(|| KeymapError::UnknownKey { action: name.clone(), key: k.clone() })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            map.keys.insert(action, codes);
+            map.overridden.insert(action);
+        }
+        map.check_conflicts()?;
+        Ok(map)
+    }
+
+    fn check_conflicts(&self) -> Result<(), KeymapError> {
+        for (i, a) in BINDINGS.iter().enumerate() {
+            for b in &BINDINGS[i + 1..] {
+                if !a.views.iter().any(|v| b.views.contains(v)) {
+                    continue;
+                }
+                let theirs = self.keys(b.action);
+                if let Some(key) = self.keys(a.action).iter().find(|k| theirs.contains(k)) {
+                    return Err(KeymapError::Conflict { key: key_name(*key), first: a.action, second: b.action });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        self.keys.get(&action).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    pub fn is_overridden(&self, action: Action) -> bool {
+        self.overridden.contains(&action)
+    }
+
+    /// The action bound to `code` in `view`.
+    pub fn action_for(&self, view: View, code: KeyCode) -> Option<Action> {
+        BINDINGS
+            .iter()
+            .find(|b| b.views.contains(&view) && self.keys(b.action).contains(&code))
+            .map(|b| b.action)
+    }
+}
+
+pub fn key_name(code: KeyCode) -> String {
+    match code {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Up => "↑".to_string(),
+        KeyCode::Down => "↓".to_string(),
+        KeyCode::Left => "←".to_string(),
+        KeyCode::Right => "→".to_string(),
+        KeyCode::Enter => "Enter".to_string(),
+        KeyCode::Tab => "Tab".to_string(),
+        KeyCode::Esc => "Esc".to_string(),
+        other => format!("{:?}", other),
+    }
+}
+
+/// Key names joined with ` / `; a lowercase letter whose uppercase form is also bound is
+/// shown once, in uppercase.
+pub fn keys_display(keys: &[KeyCode]) -> String {
+    keys.iter()
+        .filter(|k| match k {
+            KeyCode::Char(c) if c.is_ascii_lowercase() => {
+                !keys.contains(&KeyCode::Char(c.to_ascii_uppercase()))
+            }
+            _ => true,
+        })
+        .map(|k| key_name(*k))
+        .collect::<Vec<_>>()
+        .join(" / ")
+}
+
+/// Title of a popup menu driven by the kill and quit keys, showing them as currently bound,
+/// e.g. ` Send signal  Enter=send  Q / Esc=cancel `.
+pub fn menu_title(keymap: &Keymap, name: &str, confirm: &str) -> String {
+    format!(
+        " {}  {}={}  {}=cancel ",
+        name,
+        keys_display(keymap.keys(Action::KillSelected)),
+        confirm,
+        keys_display(keymap.keys(Action::Quit)),
+    )
+}
+
+/// Key hints for a view title, showing the keys as currently bound, e.g.
+/// `Tab=detail view  F=switch filter`. A hint for one action lists all of its keys (like
+/// `menu_title`), one for several actions the first key of each (like the footer); hints
+/// whose actions have no keys left are skipped.
+pub fn title_hints(keymap: &Keymap, hints: &[(&[Action], &str)]) -> String {
+    hints
+        .iter()
+        .filter_map(|&(actions, label)| {
+            let keys = match actions {
+                [action] => keys_display(keymap.keys(*action)),
+                _ => actions
+                    .iter()
+                    .filter_map(|&a| keys_display(keymap.keys(a)).split(" / ").next().map(str::to_string))
+                    .filter(|k| !k.is_empty())
+                    .collect::<Vec<_>>()
+                    .join("/"),
+            };
+            (!keys.is_empty()).then(|| format!("{}={}", keys, label))
+        })
+        .collect::<Vec<_>>()
+        .join("  ")
+}
+
+/// Footer entries for the current view: `(first key, label, action)`.
+pub fn footer(state: &AppState) -> Vec<(String, &'static str, Action)> {
+    BINDINGS
+        .iter()
+        .filter(|b| b.views.contains(&state.view) && b.action.available(state))
+        .filter_map(|b| {
+            let label = b.footer?;
+            let keys = keys_display(state.keymap.keys(b.action));
+            let first = keys.split(" / ").next().filter(|k| !k.is_empty())?.to_string();
+            Some((first, label, b.action))
+        })
+        .collect()
+}
+
+/// Bindings grouped for the help overlay: those available everywhere first, then per view.
+pub fn sections() -> Vec<(&'static str, Vec<&'static Binding>)> {
+    let mut sections = vec![("All views", BINDINGS.iter().filter(|b| b.views == ALL).collect())];
+    for (view, title) in [
+        (View::List, "List view"),
+        (View::Detail, "Detail view"),
+        (View::Tree, "Tree view"),
+        (View::Ports, "Port view"),
+    ] {
+        let bindings: Vec<&Binding> = BINDINGS
+            .iter()
+            .filter(|b| b.views != ALL && b.views.contains(&view))
+            .collect();
+        if !bindings.is_empty() {
+            sections.push((title, bindings));
+        }
+    }
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_have_no_conflicts() {
+        assert_eq!(Keymap::default().check_conflicts(), Ok(()));
+    }
+
+    #[test]
+    fn test_action_for() {
+        let keymap = Keymap::default();
+        assert_eq!(keymap.action_for(View::Tree, KeyCode::Char('S')), Some(Action::SelectSubtree));
+        assert_eq!(keymap.action_for(View::List, KeyCode::Char('s')), None);
+        assert_eq!(keymap.action_for(View::Ports, KeyCode::Esc), Some(Action::Quit));
+    }
+
+    fn table(entries: &[(&str, &[&str])]) -> BTreeMap<String, Vec<String>> {
+        entries
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.iter().map(|s| s.to_string()).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn test_keymap_overrides() {
+        let keymap = Keymap::from_config(&table(&[("kill_selected", &["x", "Delete"]), ("select_subtree", &["a"])])).unwrap();
+        assert_eq!(keymap.action_for(View::List, KeyCode::Char('x')), Some(Action::KillSelected));
+        assert_eq!(keymap.action_for(View::List, KeyCode::Enter), None);
+        assert_eq!(keymap.action_for(View::Tree, KeyCode::Delete), Some(Action::KillSelected));
+        // select_all is not bound in the tree view, so reusing `a` there is fine.
+        assert_eq!(keymap.action_for(View::Tree, KeyCode::Char('a')), Some(Action::SelectSubtree));
+        assert!(keymap.is_overridden(Action::KillSelected));
+        assert!(!keymap.is_overridden(Action::Quit));
+    }
+
+    #[test]
+    fn test_keymap_conflict() {
+        let err = Keymap::from_config(&table(&[("kill_selected", &["k"])])).unwrap_err();
+        assert_eq!(err, KeymapError::Conflict { key: "k".to_string(), first: Action::MoveUp, second: Action::KillSelected });
+        assert!(Keymap::from_config(&table(&[("kill_selected", &["k"]), ("move_up", &["Up"])])).is_ok());
+    }
+
+    #[test]
+    fn test_keymap_rejects_unknown_names() {
+        assert_eq!(
+            Keymap::from_config(&table(&[("explode", &["x"])])).unwrap_err(),
+            KeymapError::UnknownAction("explode".to_string())
+        );
+        assert!(matches!(
+            Keymap::from_config(&table(&[("quit", &["F13"])])),
+            Err(KeymapError::UnknownKey { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_key() {
+        assert_eq!(parse_key("x"), Some(KeyCode::Char('x')));
+        assert_eq!(parse_key("X"), Some(KeyCode::Char('X')));
+        assert_eq!(parse_key("space"), Some(KeyCode::Char(' ')));
+        assert_eq!(parse_key("PageDown"), Some(KeyCode::PageDown));
+        assert_eq!(parse_key("f5"), Some(KeyCode::F(5)));
+        assert_eq!(parse_key("ctrl"), None);
+    }
+
+    #[test]
+    fn test_keys_display() {
+        assert_eq!(keys_display(&[KeyCode::Char('a'), KeyCode::Char('A')]), "A");
+        assert_eq!(keys_display(&[KeyCode::Up, KeyCode::Char('k')]), "↑ / k");
+        assert_eq!(keys_display(&[KeyCode::Char('q'), KeyCode::Char('Q'), KeyCode::Esc]), "Q / Esc");
+    }
+
+    #[test]
+    fn test_menu_title_uses_bound_keys() {
+        let mut table = BTreeMap::new();
+        table.insert("quit".to_string(), vec!["x".to_string()]);
+        let keymap = Keymap::from_config(&table).unwrap();
+        assert_eq!(menu_title(&keymap, "Send signal", "send"), " Send signal  Enter=send  x=cancel ");
+    }
+
+    #[test]
+    fn test_title_hints_use_bound_keys() {
+        let mut table = BTreeMap::new();
+        table.insert("switch_view".to_string(), vec!["v".to_string()]);
+        table.insert("switch_filter".to_string(), vec![]);
+        let keymap = Keymap::from_config(&table).unwrap();
+        let hints = [(&[Action::SwitchView][..], "tree"), (&[Action::SwitchFilter][..], "switch filter"), (&[Action::Quit][..], "close")];
+        assert_eq!(title_hints(&keymap, &hints), "v=tree  Q / Esc=close");
+        let scroll = [Action::MoveUp, Action::MoveDown, Action::ScrollUp, Action::ScrollDown];
+        assert_eq!(title_hints(&keymap, &[(&scroll[..], "scroll")]), "↑/↓/PageUp/PageDown=scroll");
+    }
+
+    #[test]
+    fn test_readme_lists_every_binding() {
+        let readme = include_str!("../../README_EN.md");
+        for b in BINDINGS {
+            let row = format!("| `{}` | {} |", keys_display(b.keys).replace(" / ", "` / `"), b.description);
+            assert!(readme.contains(&row), "README_EN.md is missing the keybinding row {}", row);
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+use crate::killer::{Signal, Stage};
+use super::kills::{Status, GRACE};
+use super::AppState;
+
+/// Kill results panel: each target's progress through TERM / wait / KILL and its outcome.
+pub fn render(f: &mut Frame, area: Rect, state: &AppState) {
+    let Some(ref batch) = state.kills else { return };
+    let theme = &state.theme;
+
+    let lines: Vec<Line> = batch
+        .targets
+        .iter()
+        .map(|t| {
+            let (text, style) = match t.status {
+                Status::Running(_) if t.manager.is_some() => {
+                    (format!("stopping {}", t.manager.as_deref().unwrap_or_default()), theme.muted)
+                }
+                Status::Running(Stage::Sending(signal)) => (
+                    format!("sending {}", signal.name()),
+                    if signal == Signal::Kill { theme.accent } else { theme.muted },
+                ),
+                Status::Running(Stage::Waiting(waited)) => (
+                    format!(
+                        "SIGTERM sent, waiting {:.1}s / {}s",
+                        waited.as_secs_f32(),
+                        GRACE.as_secs()
+                    ),
+                    theme.muted,
+                ),
+                Status::Killed(ref signal) => match t.manager {
+                    Some(ref manager) => (format!("killed {} ({})", manager, signal), theme.score_high),
+                    None => (format!("killed ({})", signal), theme.score_high),
+                },
+                Status::Sent(signal) => (format!("sent {}", signal), theme.score_high),
+                Status::Stopped(ref manager) => (format!("stopped {}", manager), theme.score_high),
+                Status::Failed(ref e) => (format!("failed: {}", e), theme.error),
+            };
+            Line::from(vec![
+                Span::raw(format!(" {:>7}  {:<20} ", t.pid, t.name)),
+                Span::styled(text, style),
+            ])
+        })
+        .collect();
+
+    let title = if batch.is_done() {
+        format!(" {}  any key=close ", batch.summary())
+    } else {
+        match batch.signal {
+            Some(signal) => format!(" Sending {} to {} processes... ", signal.name(), batch.targets.len()),
+            None => format!(" Killing {} processes... ", batch.targets.len()),
+        }
+    };
+
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let [popup] = Layout::horizontal([Constraint::Percentage(80)]).flex(Flex::Center).areas(area);
+    let [popup] = Layout::vertical([Constraint::Length(height)]).flex(Flex::Center).areas(popup);
+
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(Clear, popup);
+    f.render_widget(paragraph, popup);
+}
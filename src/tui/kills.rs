@@ -0,0 +1,448 @@
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+use crate::container::{self, Container};
+use crate::history::{self, HistoryEntry, Trigger};
+use crate::killer::{self, Signal, Stage};
+use crate::process_info::ProcessInfo;
+use crate::supervisor::{self, Kind, Supervisor};
+use crate::systemd::{self, Unit};
+
+/// How long a process gets to exit after SIGTERM before it is sent SIGKILL.
+pub const GRACE: Duration = Duration::from_secs(3);
+
+/// Sends one process the given signal, or terminates it (TERM, then KILL after `GRACE`) when
+/// the signal is `None`, reporting progress. Returns the history entry for terminating signals.
+/// With no signal, a process with a manager (see `Manager`) has the manager stopped instead,
+/// a pm2 app the way `Pm2Action` says.
+pub type KillFn = fn(&ProcessInfo, Option<Signal>, Pm2Action, &mut dyn FnMut(Stage)) -> Result<Option<HistoryEntry>, String>;
+
+/// How the default kill takes down a pm2 app.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Pm2Action {
+    /// `pm2 stop`: the app stays in pm2's list and can be started again.
+    #[default]
+    Stop,
+    /// `pm2 delete`: the app is removed from pm2.
+    Delete,
+}
+
+/// A finished kill: the PID and its history entry (`None` for signals that do not end the
+/// process) or error message.
+pub type Outcome = (u32, Result<Option<HistoryEntry>, String>);
+
+enum Event {
+    Stage(u32, Stage),
+    Done(u32, Result<Option<HistoryEntry>, String>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Status {
+    Running(Stage),
+    Killed(String),
+    /// A signal that does not end the process was delivered.
+    Sent(&'static str),
+    /// The process's manager was stopped; holds its description.
+    Stopped(String),
+    Failed(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct KillTarget {
+    pub pid: u32,
+    pub name: String,
+    /// The manager stopped in place of killing the process.
+    pub manager: Option<String>,
+    pub status: Status,
+}
+
+/// What keeps a process alive: killing the PID only gets it restarted, so the default kill
+/// stops this instead. An explicit signal always goes to the process itself.
+pub enum Manager<'a> {
+    Container(&'a Container),
+    Unit(&'a Unit),
+    Supervisor(&'a Supervisor),
+}
+
+impl Manager<'_> {
+    pub fn of(p: &ProcessInfo, signal: Option<Signal>) -> Option<Manager<'_>> {
+        if signal.is_some() {
+            return None;
+        }
+        match (&p.container, &p.unit, &p.supervisor) {
+            (Some(c), _, _) if c.can_stop() => Some(Manager::Container(c)),
+            // Stopping a system unit needs root and takes down something set up on purpose;
+            // those processes are killed like any other.
+            (_, Some(unit), _) if unit.user => Some(Manager::Unit(unit)),
+            // Under pm2 only a known app is stopped: the supervisor itself is the pm2 daemon,
+            // and killing it would take every app down.
+            (_, _, Some(s)) if s.kind != Kind::Pm2 || s.pm2_app.is_some() => Some(Manager::Supervisor(s)),
+            _ => None,
+        }
+    }
+
+    /// `container web`, `unit dev-api.service`, `supervisor nodemon`, `pm2 app api`.
+    pub fn describe(&self) -> String {
+        match self {
+            Manager::Container(c) => format!("container {}", c.name),
+            Manager::Unit(unit) => format!("unit {}", unit.name),
+            Manager::Supervisor(s) => match s.pm2_app {
+                Some(ref app) => format!("pm2 app {}", app.name),
+                None => format!("supervisor {}", s.kind.name()),
+            },
+        }
+    }
+
+    /// What stopping the manager runs, for the confirmation: `docker stop web (nginx)`,
+    /// `systemctl --user stop dev-api.service`, `kill nodemon (PID 812)`, `pm2 stop api`.
+    pub fn command(&self) -> String {
+        match self {
+            Manager::Container(c) => format!("{} stop {}", c.runtime.name().to_lowercase(), c.display()),
+            Manager::Unit(unit) => format!("systemctl --user stop {}", unit.name),
+            Manager::Supervisor(s) => match s.pm2_app {
+                Some(ref app) => format!("pm2 stop {}", app.name),
+                None => format!("kill {}", s.display()),
+            },
+        }
+    }
+
+    /// Identifies the manager across targets, so one shared by several is stopped once.
+    pub fn key(&self) -> String {
+        match self {
+            Manager::Container(c) => format!("container {}", c.id),
+            Manager::Unit(unit) => format!("unit {}", unit.name),
+            Manager::Supervisor(s) => format!("supervisor {}", s.pid),
+        }
+    }
+
+    /// Makes sure `p`, another child of a manager already stopped for a sibling, is gone too.
+    fn finish(&self, p: &ProcessInfo) {
+        if let Manager::Supervisor(Supervisor { pm2_app: None, .. }) = self {
+            let _ = killer::terminate(p.pid, GRACE, |_| {});
+        }
+    }
+
+    /// Stops the manager and with it `p`. Only a killed supervisor has a history entry, so
+    /// undo restarts it (and through it the server).
+    fn stop(&self, p: &ProcessInfo, pm2: Pm2Action, progress: &mut dyn FnMut(Stage)) -> Result<Option<HistoryEntry>, String> {
+        match self {
+            Manager::Container(c) => container::stop(c).map(|_| None).map_err(|e| e.to_string()),
+            Manager::Unit(unit) => systemd::stop(unit).map(|_| None).map_err(|e| e.to_string()),
+            Manager::Supervisor(s) => match s.pm2_app {
+                Some(ref app) => match pm2 {
+                    Pm2Action::Stop => supervisor::pm2_stop(app),
+                    Pm2Action::Delete => supervisor::pm2_delete(app),
+                }
+                .map(|_| None),
+                None => {
+                    let entry = history::terminate(&s.as_process(p), Trigger::Tui, GRACE, progress)
+                        .map_err(|e| e.to_string())?;
+                    // Most supervisors take their child down with them; make sure.
+                    let _ = killer::terminate(p.pid, GRACE, |_| {});
+                    Ok(Some(entry))
+                }
+            },
+        }
+    }
+}
+
+/// Makes the default kill go to `p` itself rather than to its manager ("Kill PID").
+pub fn skip_manager(p: &mut ProcessInfo) {
+    match Manager::of(p, None) {
+        Some(Manager::Container(_)) => p.container = None,
+        Some(Manager::Unit(_)) => p.unit = None,
+        Some(Manager::Supervisor(_)) => p.supervisor = None,
+        None => {}
+    }
+}
+
+/// Kills (or other signals) running in the background, one thread per target, with their
+/// live progress.
+pub struct KillBatch {
+    pub targets: Vec<KillTarget>,
+    /// The signal picked by the user; `None` is the default TERM / KILL escalation.
+    pub signal: Option<Signal>,
+    rx: Receiver<Event>,
+}
+
+/// The real `KillFn`.
+pub fn send(p: &ProcessInfo, signal: Option<Signal>, pm2: Pm2Action, progress: &mut dyn FnMut(Stage)) -> Result<Option<HistoryEntry>, String> {
+    if let Some(manager) = Manager::of(p, signal) {
+        return manager.stop(p, pm2, progress);
+    }
+    match signal {
+        None => history::terminate(p, Trigger::Tui, GRACE, progress).map(Some),
+        Some(signal) if signal.terminates() => history::signal(p, Trigger::Tui, signal, GRACE, progress).map(Some),
+        Some(signal) => killer::send_signal(p.pid, signal).map(|_| None),
+    }
+    .map_err(|e| e.to_string())
+}
+
+impl KillBatch {
+    pub fn start(processes: Vec<ProcessInfo>, signal: Option<Signal>) -> Self {
+        Self::start_with(processes, signal, Pm2Action::Stop, send)
+    }
+
+    /// Like `start`, but each target is killed by `kill` instead of `send` (see `AppState::kill`).
+    pub fn start_with(processes: Vec<ProcessInfo>, signal: Option<Signal>, pm2: Pm2Action, kill: KillFn) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let first = Stage::Sending(signal.unwrap_or(Signal::Term));
+        let targets = processes
+            .iter()
+            .map(|p| KillTarget {
+                pid: p.pid,
+                name: p.name.clone(),
+                manager: Manager::of(p, signal).map(|m| m.describe()),
+                status: Status::Running(first),
+            })
+            .collect();
+        // Targets sharing a manager (two servers under one nodemon) stop it once: the first
+        // does the work and the others share its outcome.
+        let mut groups: Vec<(Option<String>, Vec<ProcessInfo>)> = Vec::new();
+        for p in processes {
+            let key = Manager::of(&p, signal).map(|m| m.key());
+            match groups.iter_mut().find(|(k, _)| key.is_some() && *k == key) {
+                Some((_, group)) => group.push(p),
+                None => groups.push((key, vec![p])),
+            }
+        }
+        for (_, group) in groups {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                let (p, rest) = group.split_first().expect("groups are never empty");
+                let mut progress = |stage| {
+                    let _ = tx.send(Event::Stage(p.pid, stage));
+                };
+                let result = kill(p, signal, pm2, &mut progress);
+                for other in rest {
+                    let shared = match result {
+                        Ok(_) => {
+                            if let Some(manager) = Manager::of(other, signal) {
+                                manager.finish(other);
+                            }
+                            Ok(None)
+                        }
+                        Err(ref e) => Err(e.clone()),
+                    };
+                    let _ = tx.send(Event::Done(other.pid, shared));
+                }
+                let _ = tx.send(Event::Done(p.pid, result));
+            });
+        }
+        Self { targets, signal, rx }
+    }
+
+    /// Applies the progress reported since the last call and returns the kills that finished.
+    pub fn poll(&mut self) -> Vec<Outcome> {
+        let mut finished = Vec::new();
+        while let Ok(event) = self.rx.try_recv() {
+            match event {
+                Event::Stage(pid, stage) => self.set_status(pid, Status::Running(stage)),
+                Event::Done(pid, result) => {
+                    let manager = self.targets.iter().find(|t| t.pid == pid).and_then(|t| t.manager.clone());
+                    let status = match (&result, manager) {
+                        (Ok(Some(entry)), _) => Status::Killed(entry.signal.clone()),
+                        (Ok(None), Some(manager)) => Status::Stopped(manager),
+                        (Ok(None), None) => Status::Sent(self.signal.map_or("", Signal::name)),
+                        (Err(e), _) => Status::Failed(e.clone()),
+                    };
+                    self.set_status(pid, status);
+                    finished.push((pid, result));
+                }
+            }
+        }
+        finished
+    }
+
+    fn set_status(&mut self, pid: u32, status: Status) {
+        if let Some(t) = self.targets.iter_mut().find(|t| t.pid == pid) {
+            t.status = status;
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.targets.iter().all(|t| !matches!(t.status, Status::Running(_)))
+    }
+
+    pub fn succeeded(&self) -> usize {
+        self.targets
+            .iter()
+            .filter(|t| matches!(t.status, Status::Killed(_) | Status::Sent(_) | Status::Stopped(_)))
+            .count()
+    }
+
+    pub fn managers_stopped(&self) -> usize {
+        self.targets.iter().filter(|t| matches!(t.status, Status::Stopped(_))).count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.targets.iter().filter(|t| matches!(t.status, Status::Failed(_))).count()
+    }
+
+    /// "Killed 2, failed 1", "Killed 1, stopped 1 service" (containers and units) or
+    /// "Sent SIGSTOP to 2" for signals that do not end the process.
+    pub fn summary(&self) -> String {
+        let summary = match (self.signal, self.managers_stopped()) {
+            (Some(signal), _) if !signal.terminates() => format!("Sent {} to {}", signal.name(), self.succeeded()),
+            (_, 0) => format!("Killed {}", self.succeeded()),
+            (_, 1) => format!("Killed {}, stopped 1 service", self.succeeded() - 1),
+            (_, stopped) => format!("Killed {}, stopped {} services", self.succeeded() - stopped, stopped),
+        };
+        match self.failed() {
+            0 => summary,
+            failed => format!("{}, failed {}", summary, failed),
+        }
+    }
+
+    /// A single successful kill is reported in the footer; anything else gets the panel.
+    pub fn needs_panel(&self) -> bool {
+        self.targets.len() > 1 || self.failed() > 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::killer::KillError;
+
+    /// Even PIDs need SIGKILL, odd PIDs cannot be killed; managers are stopped.
+    fn fake_kill(p: &ProcessInfo, signal: Option<Signal>, _: Pm2Action, progress: &mut dyn FnMut(Stage)) -> Result<Option<HistoryEntry>, String> {
+        if p.pid % 2 == 1 {
+            return Err(format!("Permission denied killing PID {}", p.pid));
+        }
+        if signal.is_some() || Manager::of(p, signal).is_some() {
+            return Ok(None);
+        }
+        progress(Stage::Waiting(Duration::from_millis(10)));
+        progress(Stage::Sending(Signal::Kill));
+        Ok(Some(HistoryEntry::new(p, Vec::new(), Trigger::Tui, Signal::Kill, &Ok::<_, KillError>("SIGKILL"))))
+    }
+
+    fn run(pids: &[u32], signal: Option<Signal>) -> (KillBatch, Vec<Outcome>) {
+        let processes = pids
+            .iter()
+            .map(|&pid| ProcessInfo { pid, name: format!("p{}", pid), ..Default::default() })
+            .collect();
+        run_processes(processes, signal)
+    }
+
+    fn run_processes(processes: Vec<ProcessInfo>, signal: Option<Signal>) -> (KillBatch, Vec<Outcome>) {
+        let mut batch = KillBatch::start_with(processes, signal, Pm2Action::Stop, fake_kill);
+        let mut finished = Vec::new();
+        for _ in 0..100 {
+            finished.extend(batch.poll());
+            if batch.is_done() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        (batch, finished)
+    }
+
+    #[test]
+    fn test_batch_collects_outcomes() {
+        let (batch, finished) = run(&[2, 3, 4], None);
+        assert!(batch.is_done());
+        assert_eq!(finished.len(), 3);
+        assert_eq!(batch.summary(), "Killed 2, failed 1");
+        assert_eq!(batch.targets[0].status, Status::Killed("SIGKILL".to_string()));
+        assert!(matches!(batch.targets[1].status, Status::Failed(ref e) if e.contains("Permission denied")));
+        assert!(batch.needs_panel());
+    }
+
+    #[test]
+    fn test_single_success_needs_no_panel() {
+        let (batch, _) = run(&[2], None);
+        assert!(batch.is_done());
+        assert!(!batch.needs_panel());
+    }
+
+    #[test]
+    fn test_non_terminating_signal() {
+        let (batch, finished) = run(&[2, 4], Some(Signal::Stop));
+        assert!(finished.iter().all(|(_, r)| matches!(r, Ok(None))));
+        assert_eq!(batch.targets[0].status, Status::Sent("SIGSTOP"));
+        assert_eq!(batch.summary(), "Sent SIGSTOP to 2");
+    }
+
+    #[test]
+    fn test_container_is_stopped_instead_of_proxy() {
+        let web = container::Container {
+            id: "abc123".to_string(),
+            name: "web".to_string(),
+            image: None,
+            compose_project: None,
+            runtime: container::Runtime::Docker,
+            ports: vec![8080],
+            socket: Some("/var/run/docker.sock".into()),
+        };
+        let proxy = ProcessInfo { pid: 2, name: "docker-proxy".to_string(), container: Some(web), ..Default::default() };
+        let node = ProcessInfo { pid: 4, name: "node".to_string(), ..Default::default() };
+
+        let (batch, _) = run_processes(vec![proxy.clone(), node], None);
+        assert_eq!(batch.targets[0].status, Status::Stopped("container web".to_string()));
+        assert_eq!(batch.summary(), "Killed 1, stopped 1 service");
+
+        // An explicit signal goes to the process itself.
+        let (batch, _) = run_processes(vec![proxy], Some(Signal::Stop));
+        assert_eq!(batch.targets[0].manager, None);
+        assert_eq!(batch.targets[0].status, Status::Sent("SIGSTOP"));
+    }
+
+    #[test]
+    fn test_unit_is_stopped_instead_of_process() {
+        let unit = Unit { name: "dev-api.service".to_string(), user: true };
+        let p = ProcessInfo { pid: 2, name: "node".to_string(), unit: Some(unit), ..Default::default() };
+        let (batch, _) = run_processes(vec![p], None);
+        assert_eq!(batch.targets[0].status, Status::Stopped("unit dev-api.service".to_string()));
+        assert!(!batch.needs_panel());
+    }
+
+    #[test]
+    fn test_system_unit_process_is_killed() {
+        let unit = Unit { name: "nginx.service".to_string(), user: false };
+        let p = ProcessInfo { pid: 2, name: "nginx".to_string(), unit: Some(unit), ..Default::default() };
+        assert!(Manager::of(&p, None).is_none());
+        let (batch, _) = run_processes(vec![p], None);
+        assert_eq!(batch.targets[0].manager, None);
+        assert!(matches!(batch.targets[0].status, Status::Killed(_)));
+    }
+
+    #[test]
+    fn test_pm2_daemon_is_never_the_manager() {
+        let daemon = Supervisor { kind: Kind::Pm2, pid: 9, name: "PM2 v5.3.0: God Daemon".to_string(), cmd: vec![], cwd: None, pm2_app: None };
+        let p = ProcessInfo { pid: 2, name: "node".to_string(), supervisor: Some(daemon.clone()), ..Default::default() };
+        let (batch, _) = run_processes(vec![p.clone()], None);
+        assert_eq!(batch.targets[0].manager, None);
+        assert!(matches!(batch.targets[0].status, Status::Killed(_)));
+
+        let app = supervisor::Pm2App { name: "api".to_string(), id: 0 };
+        let p = ProcessInfo { supervisor: Some(Supervisor { pm2_app: Some(app), ..daemon }), ..p };
+        assert_eq!(Manager::of(&p, None).map(|m| m.command()), Some("pm2 stop api".to_string()));
+        let (batch, _) = run_processes(vec![p], None);
+        assert_eq!(batch.targets[0].status, Status::Stopped("pm2 app api".to_string()));
+    }
+
+    #[test]
+    fn test_shared_manager_is_stopped_once() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static STOPS: AtomicUsize = AtomicUsize::new(0);
+        fn counting_kill(p: &ProcessInfo, signal: Option<Signal>, pm2: Pm2Action, progress: &mut dyn FnMut(Stage)) -> Result<Option<HistoryEntry>, String> {
+            STOPS.fetch_add(1, Ordering::SeqCst);
+            fake_kill(p, signal, pm2, progress)
+        }
+
+        let unit = Unit { name: "dev-api.service".to_string(), user: true };
+        let processes = [2, 4]
+            .map(|pid| ProcessInfo { pid, name: "node".to_string(), unit: Some(unit.clone()), ..Default::default() });
+        let mut batch = KillBatch::start_with(processes.to_vec(), None, Pm2Action::Stop, counting_kill);
+        let mut finished = Vec::new();
+        while !batch.is_done() {
+            finished.extend(batch.poll());
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(STOPS.load(Ordering::SeqCst), 1);
+        assert_eq!(finished.len(), 2);
+        assert!(batch.targets.iter().all(|t| t.status == Status::Stopped("unit dev-api.service".to_string())));
+    }
+}
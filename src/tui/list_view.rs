@@ -1,13 +1,15 @@
 use ratatui::{
-    layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    layout::{Constraint, Direction, Flex, Layout, Rect},
     widgets::{Block, Borders, Cell, Row, Table, TableState, Paragraph},
     Frame,
 };
 use crate::filter::{score_display, FilterMode};
-use super::AppState;
+use crate::process_info::memory_display;
+use super::groups::{Group, GroupRow};
+use super::keys::{self, Action};
+use super::{mouse, AppState, SortColumn};
 
-pub fn render(f: &mut Frame, area: Rect, state: &AppState) {
+pub fn render(f: &mut Frame, area: Rect, state: &mut AppState) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Min(5), Constraint::Length(2)])
@@ -17,75 +19,140 @@ pub fn render(f: &mut Frame, area: Rect, state: &AppState) {
     render_footer(f, chunks[1], state);
 }
 
-fn render_table(f: &mut Frame, area: Rect, state: &AppState) {
+const COLUMNS: [(&str, Option<SortColumn>); 6] = [
+    ("  ", None),
+    ("PID", Some(SortColumn::Pid)),
+    ("Name", Some(SortColumn::Name)),
+    ("Ports", Some(SortColumn::Ports)),
+    ("Score", Some(SortColumn::Score)),
+    ("Command", Some(SortColumn::Command)),
+];
+
+const WIDTHS: [Constraint; 6] = [
+    Constraint::Length(5),
+    Constraint::Length(8),
+    Constraint::Length(12),
+    Constraint::Length(16),
+    Constraint::Length(7),
+    Constraint::Min(20),
+];
+
+fn render_table(f: &mut Frame, area: Rect, state: &mut AppState) {
     let mode_str = match state.filter_mode {
         FilterMode::Strict => "Strict: dev runtimes only",
         FilterMode::Loose => "Loose: all listening processes",
     };
-    let title = format!(" ccpclean  [{}]  Tab=detail view  F=switch filter ", mode_str);
-
-    let header = Row::new(vec![
-        Cell::from("  "),
-        Cell::from("PID"),
-        Cell::from("Name"),
-        Cell::from("Ports"),
-        Cell::from("Score"),
-        Cell::from("Command"),
-    ])
-    .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD));
+    let hints = keys::title_hints(
+        &state.keymap,
+        &[(&[Action::SwitchView], "detail view"), (&[Action::SwitchFilter], "switch filter"), (&[Action::ToggleGroup], "group by project")],
+    );
+    let title = format!(" ccpclean  [{}]  {} ", mode_str, hints);
 
-    let rows: Vec<Row> = state
-        .processes
-        .iter()
-        .enumerate()
-        .map(|(i, p)| {
-            let is_selected = i == state.selected_index;
-            let is_checked = state.checked[i];
-
-            let checkbox = if is_checked { "[x]" } else { "[ ]" };
-            let cmd_preview = p.cmd.get(1).map(|s| s.as_str()).unwrap_or("");
-
-            let row_style = if is_selected {
-                Style::default().bg(Color::DarkGray)
-            } else {
-                Style::default()
-            };
-
-            Row::new(vec![
-                Cell::from(checkbox),
-                Cell::from(p.pid.to_string()),
-                Cell::from(p.name.clone()),
-                Cell::from(p.ports_display()),
-                Cell::from(score_display(p.score)),
-                Cell::from(cmd_preview.to_string()),
-            ])
-            .style(row_style)
-        })
-        .collect();
+    let header = Row::new(COLUMNS.iter().map(|(name, col)| {
+        if *col == Some(state.sort_column) {
+            let descending = state.sort_column.descending_by_default() != state.sort_reversed;
+            Cell::from(format!("{} {}", name, if descending { "▼" } else { "▲" }))
+        } else {
+            Cell::from(*name)
+        }
+    }))
+    .style(state.theme.header);
 
-    let widths = [
-        Constraint::Length(5),
-        Constraint::Length(8),
-        Constraint::Length(12),
-        Constraint::Length(16),
-        Constraint::Length(7),
-        Constraint::Min(20),
-    ];
+    let selected = if state.grouped { state.group_selected } else { state.selected_index };
+    let rows: Vec<Row> = if state.grouped {
+        state
+            .group_rows()
+            .iter()
+            .enumerate()
+            .map(|(i, row)| match row {
+                GroupRow::Header(g) => group_row(state, g, i == selected),
+                GroupRow::Process(pid) => match state.processes.iter().position(|p| p.pid == *pid) {
+                    Some(idx) => process_row(state, idx, i == selected),
+                    None => Row::new(vec![Cell::from("")]),
+                },
+            })
+            .collect()
+    } else {
+        (0..state.processes.len())
+            .map(|i| process_row(state, i, i == selected))
+            .collect()
+    };
 
-    let table = Table::new(rows, widths)
+    let row_count = rows.len();
+    let table = Table::new(rows, WIDTHS)
         .header(header)
         .block(Block::default().borders(Borders::ALL).title(title));
 
-    let mut table_state = TableState::default().with_selected(Some(state.selected_index));
+    let mut table_state = TableState::default().with_selected(Some(selected));
     f.render_stateful_widget(table, area, &mut table_state);
+
+    // Inside the border: one header line, then the rows.
+    let inner = Block::default().borders(Borders::ALL).inner(area);
+    let columns = Layout::horizontal(WIDTHS).flex(Flex::Start).spacing(1).split(inner);
+    state.hits.headers = columns
+        .iter()
+        .zip(COLUMNS.iter())
+        .filter_map(|(r, (_, col))| col.map(|c| (Rect { height: 1, ..*r }, c)))
+        .collect();
+    let rows_area = Rect { y: inner.y + 1, height: inner.height.saturating_sub(1), ..inner };
+    state.hits.rows = Some((rows_area, table_state.offset()));
+    state.hits.row_count = row_count;
+    state.hits.checkbox_width = columns[0].width;
 }
 
-fn render_footer(f: &mut Frame, area: Rect, state: &AppState) {
-    let msg = if let Some(ref s) = state.status_message {
-        s.clone()
+fn process_row(state: &AppState, idx: usize, is_selected: bool) -> Row<'static> {
+    let p = &state.processes[idx];
+    let checkbox = if state.checked[idx] { "[x]" } else { "[ ]" };
+    let cmd_preview = p.cmd.get(1).map(|s| s.as_str()).unwrap_or("");
+    let pid = if state.grouped { format!("  {}", p.pid) } else { p.pid.to_string() };
+
+    let mut row_style = state.theme.process(p, state.protect.is_protected(p));
+    if is_selected {
+        row_style = row_style.patch(state.theme.selected);
+    }
+
+    Row::new(vec![
+        Cell::from(checkbox),
+        Cell::from(pid),
+        Cell::from(p.name_display()),
+        Cell::from(p.ports_display()),
+        Cell::from(score_display(p.score)).style(state.theme.score(p.score)),
+        Cell::from(cmd_preview.to_string()),
+    ])
+    .style(row_style)
+}
+
+/// Project header: member count, combined ports and memory, and the project directory.
+fn group_row(state: &AppState, g: &Group, is_selected: bool) -> Row<'static> {
+    let checked = g.pids.iter().filter(|&&pid| state.is_checked(pid)).count();
+    let checkbox = if checked == 0 {
+        "[ ]"
+    } else if checked == g.pids.len() {
+        "[x]"
     } else {
-        " Space=select  A=all  Enter=kill selected  F=switch filter  Tab=detail view  Q=quit".to_string()
+        "[-]"
     };
-    let p = Paragraph::new(msg).style(Style::default().fg(Color::DarkGray));
+    let fold = if g.expanded { "▾" } else { "▸" };
+    let ports = g.ports.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(", ");
+
+    let mut style = state.theme.group;
+    if is_selected {
+        style = style.patch(state.theme.selected);
+    }
+
+    Row::new(vec![
+        Cell::from(checkbox),
+        Cell::from(fold),
+        Cell::from(format!("{} procs", g.pids.len())),
+        Cell::from(ports),
+        Cell::from(memory_display(g.memory_kb)),
+        Cell::from(g.label.clone()),
+    ])
+    .style(style)
+}
+
+fn render_footer(f: &mut Frame, area: Rect, state: &mut AppState) {
+    let msg = mouse::footer(area, state);
+    let p = Paragraph::new(msg).style(state.theme.muted);
     f.render_widget(p, area);
 }
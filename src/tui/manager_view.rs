@@ -0,0 +1,87 @@
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState},
+    Frame,
+};
+use crate::process_info::ProcessInfo;
+use super::{keys, kills::Manager, AppState};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ManagerChoice {
+    /// Stop each target's manager (`docker stop`, `systemctl --user stop`, kill the
+    /// supervisor, `pm2 stop`), which takes the process down for good.
+    Stop,
+    /// Like `Stop`, but pm2 apps are removed from pm2 with `pm2 delete`.
+    Pm2Delete,
+    /// Kill only the process; its manager may start it again.
+    KillProcess,
+}
+
+/// The choices offered for `targets`, in menu order; deleting is only offered for pm2 apps.
+pub fn choices(targets: &[ProcessInfo]) -> Vec<ManagerChoice> {
+    let pm2 = managers(targets).iter().any(|m| matches!(m, Manager::Supervisor(s) if s.pm2_app.is_some()));
+    let mut choices = vec![ManagerChoice::Stop];
+    if pm2 {
+        choices.push(ManagerChoice::Pm2Delete);
+    }
+    choices.push(ManagerChoice::KillProcess);
+    choices
+}
+
+/// The managers the default kill of `targets` would stop, each once.
+fn managers(targets: &[ProcessInfo]) -> Vec<Manager<'_>> {
+    let mut managers: Vec<Manager> = Vec::new();
+    for manager in targets.iter().filter_map(|p| Manager::of(p, None)) {
+        if !managers.iter().any(|m| m.key() == manager.key()) {
+            managers.push(manager);
+        }
+    }
+    managers
+}
+
+/// Confirmation before a kill stops the managers (containers, systemd units, supervisors) of
+/// the targets, naming each one.
+pub fn render(f: &mut Frame, area: Rect, state: &AppState) {
+    let Some((selected, ref targets)) = state.manager_confirm else { return };
+
+    let managers = managers(targets);
+    let commands: Vec<String> = managers.iter().map(Manager::command).collect();
+    let deletes: Vec<String> = managers
+        .iter()
+        .filter_map(|m| match m {
+            Manager::Supervisor(s) => s.pm2_app.as_ref().map(|app| format!("pm2 delete {}", app.name)),
+            _ => None,
+        })
+        .collect();
+
+    let items: Vec<ListItem> = choices(targets)
+        .into_iter()
+        .map(|choice| {
+            let (label, description) = match choice {
+                ManagerChoice::Stop => ("Stop", commands.join(", ")),
+                ManagerChoice::Pm2Delete => ("Delete", format!("{}; removes it from pm2", deletes.join(", "))),
+                ManagerChoice::KillProcess => ("Kill PID", "kill the process only; it may be restarted".to_string()),
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(format!(" {:<10}", label), state.theme.accent),
+                Span::styled(description, state.theme.muted),
+            ]))
+        })
+        .collect();
+
+    let height = (items.len() as u16 + 2).min(area.height);
+    let [popup] = Layout::horizontal([Constraint::Length(76)]).flex(Flex::Center).areas(area);
+    let [popup] = Layout::vertical([Constraint::Length(height)]).flex(Flex::Center).areas(popup);
+
+    let name = match managers.as_slice() {
+        [manager] => format!("Stop {}?", manager.describe()),
+        _ => format!("Stop {} managers?", managers.len()),
+    };
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(keys::menu_title(&state.keymap, &name, "run")))
+        .highlight_style(state.theme.selected);
+    let mut list_state = ListState::default().with_selected(Some(selected));
+    f.render_widget(Clear, popup);
+    f.render_stateful_widget(list, popup, &mut list_state);
+}
@@ -0,0 +1,132 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+use crate::process_info::ProcessInfo;
+use crate::scanner;
+
+/// How often the TUI samples the listening processes.
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Samples kept per process: four minutes at `SAMPLE_INTERVAL`.
+pub const HISTORY_LEN: usize = 120;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Sample {
+    /// Share of one core; above 100 when several cores are busy.
+    pub cpu_percent: f32,
+    pub memory_kb: u64,
+    pub connections: usize,
+}
+
+/// Rolling resource history of every listening process while the TUI is open, whatever the
+/// filter, so switching filters keeps the sparklines.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    history: HashMap<u32, VecDeque<Sample>>,
+}
+
+impl Metrics {
+    /// Appends one round of samples, dropping the oldest beyond `HISTORY_LEN` and forgetting
+    /// processes that were not sampled (exited or killed). Callers sample the full scan, not
+    /// the filtered list.
+    pub fn record(&mut self, samples: &[(u32, Sample)]) {
+        self.history.retain(|pid, _| samples.iter().any(|(p, _)| p == pid));
+        for &(pid, sample) in samples {
+            let history = self.history.entry(pid).or_default();
+            if history.len() == HISTORY_LEN {
+                history.pop_front();
+            }
+            history.push_back(sample);
+        }
+    }
+
+    pub fn get(&self, pid: u32) -> Option<&VecDeque<Sample>> {
+        self.history.get(&pid)
+    }
+}
+
+/// Reads CPU, memory and connections of the listening processes every `SAMPLE_INTERVAL`. Keeps
+/// its `System` between rounds, since CPU usage is measured from one refresh to the next.
+#[derive(Default)]
+pub struct Sampler {
+    sys: System,
+    last: Option<Instant>,
+}
+
+impl Sampler {
+    pub fn due(&self) -> bool {
+        self.last.is_none_or(|t| t.elapsed() >= SAMPLE_INTERVAL)
+    }
+
+    /// Samples the processes in `processes` that listen on a port. A process seen for the first
+    /// time is left out of this round: its CPU usage has no previous refresh to compare with
+    /// and always reads 0.
+    pub fn sample(&mut self, processes: &[ProcessInfo]) -> Vec<(u32, Sample)> {
+        self.last = Some(Instant::now());
+        let processes: Vec<&ProcessInfo> = processes.iter().filter(|p| !p.ports.is_empty()).collect();
+        let pids: Vec<Pid> = processes.iter().map(|p| Pid::from_u32(p.pid)).collect();
+        let known: HashSet<Pid> = pids.iter().copied().filter(|&pid| self.sys.process(pid).is_some()).collect();
+        self.sys.refresh_processes_specifics(
+            ProcessesToUpdate::Some(&pids),
+            true,
+            ProcessRefreshKind::new().with_cpu().with_memory(),
+        );
+        let established = scanner::connection_counts();
+
+        processes
+            .iter()
+            .filter(|p| known.contains(&Pid::from_u32(p.pid)))
+            .filter_map(|p| {
+                let process = self.sys.process(Pid::from_u32(p.pid))?;
+                Some((
+                    p.pid,
+                    Sample {
+                        cpu_percent: process.cpu_usage(),
+                        memory_kb: process.memory() / 1024,
+                        connections: p.ports.iter().map(|port| established.get(port).copied().unwrap_or(0)).sum(),
+                    },
+                ))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(memory_kb: u64) -> Sample {
+        Sample { memory_kb, ..Default::default() }
+    }
+
+    #[test]
+    fn test_record_caps_history() {
+        let mut m = Metrics::default();
+        for i in 0..HISTORY_LEN as u64 + 5 {
+            m.record(&[(1, sample(i))]);
+        }
+        let history = m.get(1).unwrap();
+        assert_eq!(history.len(), HISTORY_LEN);
+        assert_eq!(history.front().unwrap().memory_kb, 5);
+        assert_eq!(history.back().unwrap().memory_kb, HISTORY_LEN as u64 + 4);
+    }
+
+    #[test]
+    fn test_record_forgets_exited_processes() {
+        let mut m = Metrics::default();
+        m.record(&[(1, sample(10)), (2, sample(20))]);
+        m.record(&[(2, sample(30))]);
+        assert!(m.get(1).is_none());
+        assert_eq!(m.get(2).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_sampler_skips_first_round_and_non_listeners() {
+        let me = ProcessInfo { pid: std::process::id(), ports: vec![1], ..Default::default() };
+        let helper = ProcessInfo { pid: std::process::id(), ..Default::default() };
+        let mut sampler = Sampler::default();
+        assert!(sampler.sample(std::slice::from_ref(&me)).is_empty());
+        assert_eq!(sampler.sample(std::slice::from_ref(&me)).len(), 1);
+        assert!(sampler.sample(&[helper]).is_empty());
+    }
+}
@@ -1,14 +1,68 @@
 pub mod list_view;
+pub mod groups;
 pub mod detail_view;
+pub mod detail_tabs;
+pub mod command_view;
+pub mod clipboard;
+pub mod yank_view;
+pub mod pane_view;
+pub mod tree;
+pub mod tree_view;
+pub mod ports;
+pub mod port_view;
+pub mod mouse;
+pub mod keys;
+pub mod help_view;
+pub mod theme;
+pub mod kills;
+pub mod manager_view;
+pub mod metrics;
+pub mod kill_view;
+pub mod signal_view;
 pub mod runner;
 
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::sync::mpsc::{Receiver, TryRecvError};
+use crate::history::HistoryEntry;
+use crate::killer::Signal;
+use crate::multiplexer::Pane;
 use crate::process_info::ProcessInfo;
 use crate::filter::{apply_filter, FilterMode};
+use groups::GroupRow;
+use crate::config::Protect;
+use keys::Keymap;
+use detail_tabs::{DetailTab, Inspection};
+use kills::{KillBatch, KillFn, Manager, Pm2Action};
+use metrics::{Metrics, Sample};
+use theme::Theme;
+use mouse::HitMap;
+use ports::PortRow;
+use tree::TreeRow;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum View {
     List,
     Detail,
+    Tree,
+    Ports,
+}
+
+/// List view column that orders `AppState::processes`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SortColumn {
+    Pid,
+    Name,
+    Ports,
+    Score,
+    Command,
+}
+
+impl SortColumn {
+    /// Score sorts highest first; everything else ascending.
+    pub fn descending_by_default(self) -> bool {
+        self == SortColumn::Score
+    }
 }
 
 pub struct AppState {
@@ -20,6 +74,65 @@ pub struct AppState {
     pub filter_mode: FilterMode,
     pub status_message: Option<String>,
     pub should_quit: bool,
+    /// Processes killed by the most recent kill action, for undo.
+    pub last_killed: Vec<HistoryEntry>,
+    /// The undo running in the background, which sends its status message once every
+    /// process has been relaunched.
+    pub restarts: Option<Receiver<String>>,
+    /// Selected row in the tree view, which also shows non-listening helpers.
+    pub tree_selected: usize,
+    /// PIDs whose children are hidden in the tree view.
+    pub collapsed: HashSet<u32>,
+    /// Selected row in the port view.
+    pub port_selected: usize,
+    /// List view shows processes under per-project group headers.
+    pub grouped: bool,
+    /// Selected row of the grouped list view, headers included.
+    pub group_selected: usize,
+    /// Keys of groups whose members are hidden.
+    pub collapsed_groups: HashSet<String>,
+    pub sort_column: SortColumn,
+    pub sort_reversed: bool,
+    /// Clickable regions of the last drawn frame.
+    pub hits: HitMap,
+    pub show_help: bool,
+    pub keymap: Keymap,
+    pub theme: Theme,
+    /// The config's `[protect]` rules; matching processes are highlighted.
+    pub protect: Protect,
+    /// The running or last finished kill batch, shown in the results panel until dismissed.
+    pub kills: Option<KillBatch>,
+    /// Signal sent by the kill action instead of the TERM / KILL escalation (`--signal`).
+    pub signal: Option<Signal>,
+    /// Cursor in the signal menu (an index into `Signal::ALL`) while it is open.
+    pub signal_menu: Option<usize>,
+    /// CPU / memory / connection history of the listed processes, for the detail view.
+    pub metrics: Metrics,
+    pub detail_tab: DetailTab,
+    /// Lazily read details of the process shown in the detail view.
+    pub inspection: Option<Inspection>,
+    /// First visible line of the detail panel; clamped to the content while drawing.
+    pub detail_scroll: u16,
+    /// Scroll offset of the full-screen command inspector while it is open.
+    pub command_inspector: Option<u16>,
+    /// Cursor in the yank menu (an index into `clipboard::fields`) while it is open.
+    pub yank_menu: Option<usize>,
+    /// Everything yanked this session as `(label, value)`, printed on exit in case the
+    /// terminal ignored OSC 52.
+    pub yanked: Vec<(String, String)>,
+    /// Whether `yanked` is printed on exit (`[clipboard] print_on_exit`).
+    pub print_yanked: bool,
+    /// Cursor in the pane menu (an index into `pane_view::ITEMS`) while it is open.
+    pub pane_menu: Option<usize>,
+    /// Pane chosen for Close in the pane menu, waiting for confirmation.
+    pub pane_close: Option<Pane>,
+    /// tmux / screen session to attach to once the TUI has exited.
+    pub attach: Option<Pane>,
+    /// Kill targets waiting for confirmation because the kill would stop their managers
+    /// (containers, systemd units, supervisors), with the cursor in `manager_view::choices`.
+    pub manager_confirm: Option<(usize, Vec<ProcessInfo>)>,
+    /// How kill batches kill each target; `kills::send` outside of tests.
+    pub kill: KillFn,
 }
 
 impl AppState {
@@ -34,37 +147,299 @@ impl AppState {
             filter_mode: FilterMode::Strict,
             status_message: None,
             should_quit: false,
+            last_killed: Vec::new(),
+            restarts: None,
+            tree_selected: 0,
+            collapsed: HashSet::new(),
+            port_selected: 0,
+            grouped: false,
+            group_selected: 0,
+            collapsed_groups: HashSet::new(),
+            sort_column: SortColumn::Score,
+            sort_reversed: false,
+            hits: HitMap::default(),
+            show_help: false,
+            keymap: Keymap::default(),
+            theme: Theme::default(),
+            protect: Protect::default(),
+            kills: None,
+            signal: None,
+            signal_menu: None,
+            metrics: Metrics::default(),
+            detail_tab: DetailTab::Overview,
+            inspection: None,
+            detail_scroll: 0,
+            command_inspector: None,
+            yank_menu: None,
+            yanked: Vec::new(),
+            print_yanked: true,
+            pane_menu: None,
+            pane_close: None,
+            attach: None,
+            manager_confirm: None,
+            kill: kills::send,
         }
     }
 
     pub fn refilter(&mut self) {
         self.processes = apply_filter(self.all_processes.clone(), self.filter_mode);
         self.checked = vec![false; self.processes.len()];
+        self.apply_sort();
         self.selected_index = self.selected_index.min(self.processes.len().saturating_sub(1));
+        self.clamp_view_selections();
+    }
+
+    /// Sorts by `column`, or reverses the order if it is already the sort column.
+    pub fn set_sort(&mut self, column: SortColumn) {
+        if self.sort_column == column {
+            self.sort_reversed = !self.sort_reversed;
+        } else {
+            self.sort_column = column;
+            self.sort_reversed = false;
+        }
+        self.apply_sort();
+    }
+
+    /// Reorders `processes`, carrying check marks and the selected process along.
+    fn apply_sort(&mut self) {
+        let checked: HashSet<u32> = self.checked_pids().into_iter().collect();
+        let current = self.processes.get(self.selected_index).map(|p| p.pid);
+        match self.sort_column {
+            SortColumn::Pid => self.processes.sort_by_key(|p| p.pid),
+            SortColumn::Name => self.processes.sort_by_cached_key(|p| p.name.to_lowercase()),
+            SortColumn::Ports => self.processes.sort_by_key(|p| p.ports.iter().min().copied().unwrap_or(u16::MAX)),
+            SortColumn::Score => self.processes.sort_by_key(|p| Reverse(p.score)),
+            SortColumn::Command => self.processes.sort_by_cached_key(|p| p.cmd.get(1).cloned().unwrap_or_default()),
+        }
+        if self.sort_reversed {
+            self.processes.reverse();
+        }
+        self.checked = self.processes.iter().map(|p| checked.contains(&p.pid)).collect();
+        if let Some(i) = current.and_then(|pid| self.processes.iter().position(|p| p.pid == pid)) {
+            self.selected_index = i;
+        }
+    }
+
+    /// Moves the cursor of the current view to `index`, e.g. for a mouse click.
+    pub fn select_row(&mut self, index: usize) {
+        let (cursor, len) = self.cursor();
+        if index < len {
+            *cursor = index;
+        }
+        self.sync_group_selection();
     }
 
     pub fn remove_processes(&mut self, pids: &[u32]) {
+        let checked: HashSet<u32> = self.checked_pids().into_iter().collect();
         self.processes.retain(|p| !pids.contains(&p.pid));
         self.all_processes.retain(|p| !pids.contains(&p.pid));
-        self.checked = vec![false; self.processes.len()];
+        self.checked = self.processes.iter().map(|p| checked.contains(&p.pid)).collect();
         self.selected_index = self.selected_index.min(self.processes.len().saturating_sub(1));
+        self.clamp_view_selections();
+    }
+
+    pub fn tree_rows(&self) -> Vec<TreeRow> {
+        tree::build_rows(&self.all_processes, &self.processes, &self.collapsed)
+    }
+
+    pub fn port_rows(&self) -> Vec<PortRow> {
+        ports::build_rows(&self.processes)
+    }
+
+    pub fn group_rows(&self) -> Vec<GroupRow> {
+        groups::build_rows(&self.all_processes, &self.processes, &self.collapsed_groups)
+    }
+
+    fn clamp_view_selections(&mut self) {
+        self.tree_selected = self.tree_selected.min(self.tree_rows().len().saturating_sub(1));
+        self.port_selected = self.port_selected.min(self.port_rows().len().saturating_sub(1));
+        self.group_selected = self.group_selected.min(self.group_rows().len().saturating_sub(1));
+    }
+
+    /// Switches the list view between flat and grouped by project, keeping the cursor on the
+    /// same process.
+    pub fn toggle_grouped(&mut self) {
+        self.grouped = !self.grouped;
+        if self.grouped {
+            let target = self.processes.get(self.selected_index).map(|p| GroupRow::Process(p.pid));
+            self.group_selected = self
+                .group_rows()
+                .iter()
+                .position(|r| Some(r) == target.as_ref())
+                .unwrap_or(0);
+        }
+    }
+
+    /// Points `selected_index` (used by the detail view) at the process under the grouped
+    /// cursor.
+    fn sync_group_selection(&mut self) {
+        if let Some(GroupRow::Process(pid)) = self.group_rows().get(self.group_selected) {
+            if let Some(i) = self.processes.iter().position(|p| p.pid == *pid) {
+                self.selected_index = i;
+            }
+        }
+    }
+
+    fn is_grouped_list(&self) -> bool {
+        self.grouped && self.view == View::List
+    }
+
+    /// Selected row index and row count of the current view.
+    fn cursor(&mut self) -> (&mut usize, usize) {
+        match self.view {
+            View::Tree => {
+                let len = self.tree_rows().len();
+                (&mut self.tree_selected, len)
+            }
+            View::Ports => {
+                let len = self.port_rows().len();
+                (&mut self.port_selected, len)
+            }
+            View::List if self.grouped => {
+                let len = self.group_rows().len();
+                (&mut self.group_selected, len)
+            }
+            View::List | View::Detail => {
+                let len = self.processes.len();
+                (&mut self.selected_index, len)
+            }
+        }
     }
 
     pub fn move_up(&mut self) {
-        if self.selected_index > 0 {
-            self.selected_index -= 1;
+        let (index, _) = self.cursor();
+        if *index > 0 {
+            *index -= 1;
         }
+        self.sync_group_selection();
     }
 
     pub fn move_down(&mut self) {
-        if self.selected_index + 1 < self.processes.len() {
-            self.selected_index += 1;
+        let (index, len) = self.cursor();
+        if *index + 1 < len {
+            *index += 1;
         }
+        self.sync_group_selection();
     }
 
     pub fn toggle_checked(&mut self) {
-        if let Some(v) = self.checked.get_mut(self.selected_index) {
-            *v = !*v;
+        match self.view {
+            View::Tree => {
+                if let Some(row) = self.tree_rows().get(self.tree_selected) {
+                    let pid = row.pid;
+                    if self.tree_killable(pid) {
+                        self.set_checked(&[pid], !self.is_checked(pid));
+                    }
+                }
+            }
+            View::Ports => {
+                if let Some(row) = self.port_rows().get(self.port_selected) {
+                    let all_checked = row.pids.iter().all(|&pid| self.is_checked(pid));
+                    self.set_checked(&row.pids, !all_checked);
+                }
+            }
+            View::List if self.grouped => {
+                let pids = groups::row_pids(&self.group_rows(), self.group_selected);
+                let all_checked = pids.iter().all(|&pid| self.is_checked(pid));
+                self.set_checked(&pids, !all_checked);
+            }
+            View::List | View::Detail => {
+                if let Some(v) = self.checked.get_mut(self.selected_index) {
+                    *v = !*v;
+                }
+            }
+        }
+    }
+
+    /// Every process holding the selected port in the port view.
+    pub fn port_owners(&self) -> Vec<ProcessInfo> {
+        let Some(row) = self.port_rows().into_iter().nth(self.port_selected) else {
+            return Vec::new();
+        };
+        self.processes
+            .iter()
+            .filter(|p| row.pids.contains(&p.pid))
+            .cloned()
+            .collect()
+    }
+
+    pub fn is_checked(&self, pid: u32) -> bool {
+        self.processes
+            .iter()
+            .position(|p| p.pid == pid)
+            .is_some_and(|i| self.checked[i])
+    }
+
+    /// Checks or unchecks the listed processes among `pids`; helpers are ignored.
+    fn set_checked(&mut self, pids: &[u32], value: bool) {
+        for (p, c) in self.processes.iter().zip(self.checked.iter_mut()) {
+            if pids.contains(&p.pid) {
+                *c = value;
+            }
+        }
+    }
+
+    /// ccpclean itself and its ancestors (the shell and terminal it runs in).
+    fn session_pids(&self) -> HashSet<u32> {
+        let mut pids = HashSet::new();
+        let mut cur = Some(std::process::id());
+        while let Some(pid) = cur.filter(|&pid| pid > 1 && pids.insert(pid)) {
+            cur = self.all_processes.iter().find(|p| p.pid == pid).and_then(|p| p.parent_pid);
+        }
+        pids
+    }
+
+    /// Whether a tree row can be checked and killed: listed, not protected and not part of the
+    /// session ccpclean runs in. Every other row is shown for context only.
+    pub fn tree_killable(&self, pid: u32) -> bool {
+        self.processes
+            .iter()
+            .find(|p| p.pid == pid)
+            .is_some_and(|p| !self.protect.is_protected(p) && !self.session_pids().contains(&pid))
+    }
+
+    /// Checks every killable process in the selected row's subtree, or unchecks them all if
+    /// they are already checked.
+    pub fn toggle_subtree(&mut self) {
+        let pids = tree::subtree_pids(&self.tree_rows(), self.tree_selected);
+        let listed: Vec<u32> = pids.into_iter().filter(|&pid| self.tree_killable(pid)).collect();
+        let all_checked = listed.iter().all(|&pid| self.is_checked(pid));
+        self.set_checked(&listed, !all_checked);
+    }
+
+    /// Members of the group under the cursor when it is on a group header.
+    pub fn current_group_pids(&self) -> Option<Vec<u32>> {
+        if !self.is_grouped_list() {
+            return None;
+        }
+        match self.group_rows().into_iter().nth(self.group_selected)? {
+            GroupRow::Header(g) => Some(g.pids),
+            GroupRow::Process(_) => None,
+        }
+    }
+
+    /// Whether Left/Right fold rows in the current view.
+    pub fn can_fold(&self) -> bool {
+        self.view == View::Tree || self.is_grouped_list()
+    }
+
+    pub fn set_expanded(&mut self, expanded: bool) {
+        if self.is_grouped_list() {
+            if let Some(GroupRow::Header(g)) = self.group_rows().into_iter().nth(self.group_selected) {
+                if expanded {
+                    self.collapsed_groups.remove(&g.key);
+                } else {
+                    self.collapsed_groups.insert(g.key);
+                }
+            }
+            return;
+        }
+        if let Some(row) = self.tree_rows().get(self.tree_selected) {
+            if expanded {
+                self.collapsed.remove(&row.pid);
+            } else if row.has_children {
+                self.collapsed.insert(row.pid);
+            }
         }
     }
 
@@ -84,14 +459,333 @@ impl AppState {
     }
 
     pub fn current_process(&self) -> Option<&ProcessInfo> {
-        self.processes.get(self.selected_index)
+        match self.view {
+            View::Tree => {
+                let pid = self.tree_rows().get(self.tree_selected)?.pid;
+                self.all_processes.iter().find(|p| p.pid == pid)
+            }
+            View::Ports => {
+                let pid = *self.port_rows().get(self.port_selected)?.pids.first()?;
+                self.processes.iter().find(|p| p.pid == pid)
+            }
+            View::List if self.grouped => match self.group_rows().get(self.group_selected)? {
+                GroupRow::Process(pid) => self.processes.iter().find(|p| p.pid == *pid),
+                GroupRow::Header(_) => None,
+            },
+            View::List | View::Detail => self.processes.get(self.selected_index),
+        }
+    }
+
+    /// Processes an action applies to: the checked ones, else the current row (a process, a
+    /// port's owners or, in the list view, a project header's members). Empty, with a status
+    /// message, when there is nothing to act on.
+    pub fn targets(&mut self) -> Vec<ProcessInfo> {
+        let mut pids = self.checked_pids();
+        if pids.is_empty() {
+            pids = match self.view {
+                View::Detail | View::Tree => self.current_process().map(|p| p.pid).into_iter().collect(),
+                View::Ports => self.port_owners().iter().map(|p| p.pid).collect(),
+                View::List => self.current_group_pids().unwrap_or_default(),
+            };
+        }
+        if self.view == View::Tree {
+            pids.retain(|&pid| self.tree_killable(pid));
+            if pids.is_empty() {
+                self.status_message = Some(" This row is shown for context only and cannot be killed".to_string());
+            }
+        }
+        if pids.is_empty() && self.view == View::List {
+            self.status_message = Some(" No processes selected (use Space to check)".to_string());
+        }
+        self.processes.iter().filter(|p| pids.contains(&p.pid)).cloned().collect()
+    }
+
+    /// Starts killing `targets` in the background (with `signal`, if set) unless a batch is
+    /// still running. A kill that would stop a manager (see `kills::Manager`) instead asks
+    /// for confirmation first.
+    pub fn start_kills(&mut self, targets: Vec<ProcessInfo>) {
+        if targets.iter().any(|p| Manager::of(p, self.signal).is_some()) {
+            self.manager_confirm = Some((0, targets));
+            return;
+        }
+        self.start_signal(targets, self.signal);
+    }
+
+    pub fn move_manager_confirm(&mut self, delta: isize) {
+        if let Some((ref mut i, ref targets)) = self.manager_confirm {
+            *i = i.saturating_add_signed(delta).min(manager_view::choices(targets).len() - 1);
+        }
+    }
+
+    /// Closes the manager confirmation and runs the kill the way the user chose: stopping the
+    /// managers (deleting pm2 apps, if picked), or killing only the processes.
+    pub fn confirm_manager_menu(&mut self) {
+        let Some((i, mut targets)) = self.manager_confirm.take() else { return };
+        let mut pm2 = Pm2Action::Stop;
+        match manager_view::choices(&targets)[i] {
+            manager_view::ManagerChoice::Stop => {}
+            manager_view::ManagerChoice::Pm2Delete => pm2 = Pm2Action::Delete,
+            manager_view::ManagerChoice::KillProcess => targets.iter_mut().for_each(kills::skip_manager),
+        }
+        self.start_batch(targets, self.signal, pm2);
+    }
+
+    /// Sends `signal` to `targets` in the background; `None` terminates them (TERM, then KILL).
+    pub fn start_signal(&mut self, targets: Vec<ProcessInfo>, signal: Option<Signal>) {
+        self.start_batch(targets, signal, Pm2Action::Stop);
+    }
+
+    fn start_batch(&mut self, targets: Vec<ProcessInfo>, signal: Option<Signal>, pm2: Pm2Action) {
+        if targets.is_empty() {
+            return;
+        }
+        if self.kills.as_ref().is_some_and(|b| !b.is_done()) {
+            self.status_message = Some(" A kill is still in progress.".to_string());
+            return;
+        }
+        if signal.is_none_or(Signal::terminates) {
+            self.last_killed.clear();
+        }
+        self.status_message = Some(match signal {
+            Some(signal) => format!(" Sending {} to {} process(es)...", signal.name(), targets.len()),
+            None => format!(" Killing {} process(es)...", targets.len()),
+        });
+        self.kills = Some(KillBatch::start_with(targets, signal, pm2, self.kill));
+    }
+
+    /// Shows the outcome of a background undo once it has finished.
+    pub fn poll_restarts(&mut self) {
+        let Some(ref rx) = self.restarts else { return };
+        match rx.try_recv() {
+            Ok(message) => {
+                self.status_message = Some(message);
+                self.restarts = None;
+            }
+            Err(TryRecvError::Disconnected) => self.restarts = None,
+            Err(TryRecvError::Empty) => {}
+        }
+    }
+
+    /// Applies kill progress: killed processes leave the list, stopped / resumed ones are
+    /// marked, and failed ones stay (or become) checked so they can be retried.
+    pub fn poll_kills(&mut self) {
+        let Some(batch) = self.kills.as_mut() else {
+            return;
+        };
+        let finished = batch.poll();
+        if finished.is_empty() {
+            return;
+        }
+        let done = batch.is_done();
+        let (signal, failed, needs_panel, summary) = (batch.signal, batch.failed(), batch.needs_panel(), batch.summary());
+        let only_manager = (batch.targets.len() == 1).then(|| batch.targets[0].manager.clone()).flatten();
+        for (pid, result) in finished {
+            match result {
+                Ok(Some(entry)) => {
+                    self.remove_processes(&[pid]);
+                    self.last_killed.push(entry);
+                }
+                // The manager was stopped and the process exits with it.
+                Ok(None) if signal.is_none() => self.remove_processes(&[pid]),
+                Ok(None) => {
+                    let stopped = signal == Some(Signal::Stop);
+                    if matches!(signal, Some(Signal::Stop | Signal::Cont)) {
+                        for p in self.processes.iter_mut().chain(self.all_processes.iter_mut()) {
+                            if p.pid == pid {
+                                p.stopped = stopped;
+                            }
+                        }
+                    }
+                    self.set_checked(&[pid], false);
+                }
+                Err(_) => self.set_checked(&[pid], true),
+            }
+        }
+        if done {
+            let undo = signal.is_none_or(Signal::terminates);
+            self.status_message = Some(match (self.last_killed.last(), only_manager) {
+                (Some(entry), Some(manager)) if !needs_panel => {
+                    format!(" Killed {} (PID {}). Press U to undo.", manager, entry.pid)
+                }
+                (Some(entry), None) if !needs_panel && undo => {
+                    format!(" Killed PID {} ({}). Press U to undo.", entry.pid, entry.signal)
+                }
+                (None, Some(manager)) if failed == 0 => format!(" Stopped {}.", manager),
+                _ if failed > 0 => format!(" {} (still checked, press Enter to retry).", summary),
+                _ if undo => format!(" {}. Press U to undo.", summary),
+                _ => format!(" {}.", summary),
+            });
+            if !needs_panel {
+                self.kills = None;
+            }
+        }
+    }
+
+    /// Stores a round of samples and refreshes the memory and connection figures they carry.
+    pub fn record_samples(&mut self, samples: &[(u32, Sample)]) {
+        self.metrics.record(samples);
+        for p in self.processes.iter_mut().chain(self.all_processes.iter_mut()) {
+            if let Some((_, sample)) = samples.iter().find(|(pid, _)| *pid == p.pid) {
+                p.memory_kb = sample.memory_kb;
+                p.connections = sample.connections;
+            }
+        }
+    }
+
+    pub fn cycle_detail_tab(&mut self, delta: isize) {
+        self.set_detail_tab(self.detail_tab.cycle(delta));
+    }
+
+    pub fn set_detail_tab(&mut self, tab: DetailTab) {
+        self.detail_tab = tab;
+        self.detail_scroll = 0;
+    }
+
+    /// Scrolls the command inspector when it is open, otherwise the detail panel.
+    pub fn scroll(&mut self, delta: i16) {
+        let offset = self.command_inspector.as_mut().unwrap_or(&mut self.detail_scroll);
+        *offset = offset.saturating_add_signed(delta);
+    }
+
+    /// Opens the command inspector for the current process.
+    pub fn open_command_inspector(&mut self) {
+        if self.current_process().is_some() {
+            self.command_inspector = Some(0);
+        } else {
+            self.status_message = Some(" No process selected".to_string());
+        }
+    }
+
+    /// Reads what the current detail tab needs for the selected process, once per process.
+    pub fn load_detail(&mut self) {
+        if self.view != View::Detail {
+            return;
+        }
+        let Some(p) = self.current_process().cloned() else {
+            self.inspection = None;
+            return;
+        };
+        if self.inspection.as_ref().is_none_or(|i| i.pid != p.pid) {
+            self.inspection = Some(Inspection::new(p.pid));
+            self.detail_scroll = 0;
+        }
+        if let Some(ref mut inspection) = self.inspection {
+            inspection.load(self.detail_tab, &p);
+        }
+    }
+
+    /// Opens the yank menu for the current process.
+    pub fn open_yank_menu(&mut self) {
+        if self.current_process().is_some() {
+            self.yank_menu = Some(0);
+        } else {
+            self.status_message = Some(" No process selected".to_string());
+        }
+    }
+
+    pub fn move_yank_menu(&mut self, delta: isize) {
+        let len = self.current_process().map_or(0, |p| clipboard::fields(p).len());
+        if let Some(ref mut i) = self.yank_menu {
+            *i = i.saturating_add_signed(delta).min(len.saturating_sub(1));
+        }
+    }
+
+    /// Closes the yank menu and returns the chosen value, remembering it for the exit summary.
+    pub fn confirm_yank_menu(&mut self) -> Option<String> {
+        let i = self.yank_menu.take()?;
+        let (label, value) = clipboard::fields(self.current_process()?).into_iter().nth(i)?;
+        self.status_message = Some(format!(" Copied {} to the clipboard: {}", label.to_lowercase(), value));
+        if self.print_yanked {
+            self.yanked.push((label.to_string(), value.clone()));
+        }
+        Some(value)
+    }
+
+    /// Opens the pane menu when the current process runs in tmux or screen.
+    pub fn open_pane_menu(&mut self) {
+        if self.current_process().is_some_and(|p| p.pane.is_some()) {
+            self.pane_menu = Some(0);
+        } else {
+            self.status_message = Some(" Not running in a tmux pane or screen window".to_string());
+        }
+    }
+
+    pub fn move_pane_menu(&mut self, delta: isize) {
+        if let Some(ref mut i) = self.pane_menu {
+            *i = i.saturating_add_signed(delta).min(pane_view::ITEMS.len() - 1);
+        }
+    }
+
+    /// Closes the pane menu and returns the pane when Jump was chosen. Close is not run
+    /// straight away: it asks for confirmation, and is refused for `ccpclean`'s own pane.
+    pub fn confirm_pane_menu(&mut self) -> Option<Pane> {
+        let i = self.pane_menu.take()?;
+        let pane = self.current_process()?.pane.clone()?;
+        match pane_view::ITEMS[i].0 {
+            pane_view::PaneAction::Kill if pane.is_own() => {
+                self.status_message = Some(format!(" Not closing {}: ccpclean is running in it", pane.display()));
+                None
+            }
+            pane_view::PaneAction::Kill => {
+                self.pane_close = Some(pane);
+                None
+            }
+            pane_view::PaneAction::Jump => Some(pane),
+        }
+    }
+
+    /// The listed processes running in the pane waiting to be closed.
+    pub fn pane_close_processes(&self) -> Vec<&ProcessInfo> {
+        let Some(ref pane) = self.pane_close else { return Vec::new() };
+        self.all_processes.iter().filter(|p| p.pane.as_ref() == Some(pane)).collect()
+    }
+
+    /// Opens the signal menu when there is something to send a signal to.
+    pub fn open_signal_menu(&mut self) {
+        if !self.targets().is_empty() {
+            self.signal_menu = Some(0);
+        }
+    }
+
+    /// Moves the signal menu cursor by `delta`, clamped to the menu.
+    pub fn move_signal_menu(&mut self, delta: isize) {
+        if let Some(ref mut i) = self.signal_menu {
+            *i = i.saturating_add_signed(delta).min(Signal::ALL.len() - 1);
+        }
+    }
+
+    /// Sends the signal under the menu cursor to the current targets and closes the menu.
+    pub fn confirm_signal_menu(&mut self) {
+        if let Some(i) = self.signal_menu.take() {
+            let targets = self.targets();
+            self.start_signal(targets, Some(Signal::ALL[i]));
+        }
+    }
+
+    /// Whether the kill results panel is on screen.
+    pub fn kill_panel_visible(&self) -> bool {
+        self.kills.as_ref().is_some_and(|b| b.needs_panel())
+    }
+
+    /// Closes the results panel once every kill has finished.
+    pub fn dismiss_kills(&mut self) {
+        if self.kills.as_ref().is_some_and(|b| b.is_done()) {
+            self.kills = None;
+        }
     }
 
     pub fn switch_view(&mut self) {
         self.view = match self.view {
             View::List => View::Detail,
-            View::Detail => View::List,
+            View::Detail => View::Tree,
+            View::Tree => View::Ports,
+            View::Ports => View::List,
         };
+        // Regions drawn for the old view must not catch clicks before the next frame.
+        self.hits = HitMap::default();
+        if self.view == View::Tree {
+            self.tree_selected = self.tree_rows().iter().position(|r| r.listed).unwrap_or(0);
+        }
     }
 }
 
@@ -104,10 +798,17 @@ mod tests {
     fn make_app() -> AppState {
         let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
         let processes = vec![
-            ProcessInfo { pid: 1, name: "node".to_string(), cmd: vec![], ports: vec![3000], start_time_secs: now, memory_kb: 0, parent_pid: None, parent_name: None, is_dev_runtime: true, score: 80 },
-            ProcessInfo { pid: 2, name: "python".to_string(), cmd: vec![], ports: vec![8000], start_time_secs: now, memory_kb: 0, parent_pid: None, parent_name: None, is_dev_runtime: true, score: 60 },
+            ProcessInfo { pid: 1, name: "node".to_string(), cmd: vec![], ports: vec![3000], start_time_secs: now, memory_kb: 0, parent_pid: None, parent_name: None, is_dev_runtime: true, score: 80, ..Default::default() },
+            ProcessInfo { pid: 2, name: "python".to_string(), cmd: vec![], ports: vec![8000], start_time_secs: now, memory_kb: 0, parent_pid: None, parent_name: None, is_dev_runtime: true, score: 60, ..Default::default() },
         ];
-        AppState::new(processes)
+        let mut app = AppState::new(processes);
+        app.kill = no_kill;
+        app
+    }
+
+    /// Stands in for `kills::send` so no test signals a real process.
+    fn no_kill(_: &ProcessInfo, _: Option<Signal>, _: Pm2Action, _: &mut dyn FnMut(crate::killer::Stage)) -> Result<Option<HistoryEntry>, String> {
+        Ok(None)
     }
 
     #[test]
@@ -154,6 +855,66 @@ mod tests {
         assert_eq!(app.checked_pids(), vec![1]);
     }
 
+    #[test]
+    fn test_remove_processes_keeps_other_checks() {
+        let mut app = make_app();
+        app.checked = vec![true, true];
+        app.remove_processes(&[1]);
+        assert_eq!(app.processes.len(), 1);
+        assert_eq!(app.checked_pids(), vec![2]);
+    }
+
+    #[test]
+    fn test_signal_menu_targets() {
+        let mut app = make_app();
+        app.open_signal_menu();
+        assert_eq!(app.signal_menu, None);
+        assert!(app.status_message.is_some());
+
+        app.checked[1] = true;
+        app.open_signal_menu();
+        app.move_signal_menu(-1);
+        assert_eq!(app.signal_menu, Some(0));
+        app.move_signal_menu(10);
+        assert_eq!(app.signal_menu, Some(Signal::ALL.len() - 1));
+        assert_eq!(app.targets().iter().map(|p| p.pid).collect::<Vec<_>>(), vec![2]);
+    }
+
+    #[test]
+    fn test_pane_close_asks_first() {
+        let pane = Pane {
+            kind: crate::multiplexer::Kind::Screen,
+            session: "12345.ccpclean-test".to_string(),
+            window: "3".to_string(),
+            pane: None,
+            id: None,
+            tty: None,
+        };
+        let mut app = make_app();
+        app.processes[0].pane = Some(pane.clone());
+        app.all_processes[0].pane = Some(pane.clone());
+
+        app.open_pane_menu();
+        app.move_pane_menu(1);
+        assert_eq!(app.confirm_pane_menu(), None);
+        assert_eq!(app.pane_menu, None);
+        assert_eq!(app.pane_close, Some(pane));
+        assert_eq!(app.pane_close_processes().iter().map(|p| p.pid).collect::<Vec<_>>(), vec![1]);
+    }
+
+    #[test]
+    fn test_scroll_targets_inspector_when_open() {
+        let mut app = make_app();
+        app.scroll(-5);
+        app.scroll(3);
+        assert_eq!(app.detail_scroll, 3);
+        app.open_command_inspector();
+        app.scroll(2);
+        assert_eq!((app.detail_scroll, app.command_inspector), (3, Some(2)));
+        app.cycle_detail_tab(1);
+        assert_eq!(app.detail_scroll, 0);
+    }
+
     #[test]
     fn test_switch_view() {
         let mut app = make_app();
@@ -161,6 +922,271 @@ mod tests {
         app.switch_view();
         assert_eq!(app.view, View::Detail);
         app.switch_view();
+        assert_eq!(app.view, View::Tree);
+        app.switch_view();
+        assert_eq!(app.view, View::Ports);
+        app.switch_view();
         assert_eq!(app.view, View::List);
     }
+
+    #[test]
+    fn test_set_sort_keeps_checks_and_selection() {
+        let mut app = make_app();
+        app.toggle_checked();
+        app.move_down();
+        app.set_sort(SortColumn::Name);
+        assert_eq!(app.sort_column, SortColumn::Name);
+        assert_eq!(app.processes[0].name, "node");
+        app.set_sort(SortColumn::Name);
+        assert!(app.sort_reversed);
+        assert_eq!(app.processes[0].name, "python");
+        assert_eq!(app.selected_index, 0);
+        assert_eq!(app.checked, vec![false, true]);
+        assert_eq!(app.checked_pids(), vec![1]);
+    }
+
+    #[test]
+    fn test_select_row_bounds() {
+        let mut app = make_app();
+        app.select_row(1);
+        assert_eq!(app.selected_index, 1);
+        app.select_row(5);
+        assert_eq!(app.selected_index, 1);
+    }
+
+    #[test]
+    fn test_grouped_list_checks_whole_project() {
+        let procs = vec![
+            ProcessInfo { pid: 1, name: "node".to_string(), project_root: Some("/src/shop".to_string()), ports: vec![3000], is_dev_runtime: true, score: 90, ..Default::default() },
+            ProcessInfo { pid: 2, name: "python".to_string(), project_root: Some("/src/blog".to_string()), ports: vec![8000], is_dev_runtime: true, score: 80, ..Default::default() },
+            ProcessInfo { pid: 3, name: "node".to_string(), project_root: Some("/src/shop".to_string()), ports: vec![3001], is_dev_runtime: true, score: 70, ..Default::default() },
+        ];
+        let mut app = AppState::new(procs);
+        app.refilter();
+        app.toggle_grouped();
+        assert_eq!(app.group_rows().len(), 5);
+        assert_eq!(app.group_selected, 1);
+        app.move_up();
+        assert!(app.current_process().is_none());
+        assert_eq!(app.current_group_pids(), Some(vec![1, 3]));
+        app.toggle_checked();
+        assert_eq!(app.checked_pids(), vec![1, 3]);
+
+        app.set_expanded(false);
+        assert_eq!(app.group_rows().len(), 3);
+        app.move_down();
+        assert_eq!(app.current_group_pids(), Some(vec![2]));
+        app.move_down();
+        assert_eq!(app.current_process().unwrap().pid, 2);
+        assert_eq!(app.selected_index, 1);
+    }
+
+    #[test]
+    fn test_port_view_selection_covers_all_owners() {
+        let procs = vec![
+            ProcessInfo { pid: 1, name: "gunicorn".to_string(), ports: vec![8000], is_dev_runtime: true, ..Default::default() },
+            ProcessInfo { pid: 2, name: "gunicorn".to_string(), ports: vec![8000], is_dev_runtime: true, ..Default::default() },
+            ProcessInfo { pid: 3, name: "node".to_string(), ports: vec![3000], is_dev_runtime: true, ..Default::default() },
+        ];
+        let mut app = AppState::new(procs);
+        app.refilter();
+        app.view = View::Ports;
+        assert_eq!(app.port_rows().len(), 2);
+        assert_eq!(app.current_process().unwrap().pid, 3);
+        app.move_down();
+        assert_eq!(app.port_selected, 1);
+        let owners: Vec<u32> = app.port_owners().iter().map(|p| p.pid).collect();
+        assert_eq!(owners, vec![1, 2]);
+        app.toggle_checked();
+        assert_eq!(app.checked_pids(), vec![1, 2]);
+        app.toggle_checked();
+        assert!(app.checked_pids().is_empty());
+    }
+
+    fn make_tree_app() -> AppState {
+        let procs = vec![
+            ProcessInfo { pid: 10, name: "zsh".to_string(), parent_pid: Some(1), ..Default::default() },
+            ProcessInfo { pid: 20, name: "npm".to_string(), parent_pid: Some(10), ..Default::default() },
+            ProcessInfo { pid: 30, name: "node".to_string(), parent_pid: Some(20), ports: vec![3000], is_dev_runtime: true, ..Default::default() },
+            ProcessInfo { pid: 31, name: "node".to_string(), parent_pid: Some(20), ports: vec![3001], is_dev_runtime: true, ..Default::default() },
+        ];
+        let mut app = AppState::new(procs);
+        app.refilter();
+        app.view = View::Tree;
+        app
+    }
+
+    #[test]
+    fn test_tree_navigation_and_current_process() {
+        let mut app = make_tree_app();
+        assert_eq!(app.tree_rows().len(), 4);
+        assert_eq!(app.current_process().unwrap().name, "zsh");
+        app.move_down();
+        app.move_down();
+        app.move_down();
+        app.move_down();
+        assert_eq!(app.tree_selected, 3);
+        assert_eq!(app.current_process().unwrap().pid, 31);
+        assert_eq!(app.selected_index, 0);
+    }
+
+    #[test]
+    fn test_switch_to_tree_selects_first_listener() {
+        let mut app = make_tree_app();
+        app.view = View::Detail;
+        app.switch_view();
+        assert_eq!(app.view, View::Tree);
+        assert_eq!(app.tree_selected, 2);
+        assert_eq!(app.current_process().unwrap().pid, 30);
+    }
+
+    #[test]
+    fn test_tree_toggle_subtree_checks_listeners_only() {
+        let mut app = make_tree_app();
+        app.toggle_subtree();
+        assert_eq!(app.checked_pids(), vec![30, 31]);
+        app.toggle_subtree();
+        assert!(app.checked_pids().is_empty());
+
+        // Space on a helper does nothing; on a listener it toggles that process.
+        app.toggle_checked();
+        assert!(app.checked_pids().is_empty());
+        app.tree_selected = 2;
+        app.toggle_checked();
+        assert_eq!(app.checked_pids(), vec![30]);
+    }
+
+    #[test]
+    fn test_tree_enter_on_ancestor_has_no_targets() {
+        let mut app = make_tree_app();
+        assert_eq!(app.current_process().unwrap().name, "zsh");
+        assert!(app.targets().is_empty());
+        app.tree_selected = 1;
+        assert_eq!(app.current_process().unwrap().name, "npm");
+        assert!(app.targets().is_empty());
+        assert!(app.status_message.as_deref().unwrap().contains("cannot be killed"));
+    }
+
+    #[test]
+    fn test_tree_protected_rows_cannot_be_killed() {
+        let mut app = make_tree_app();
+        app.protect = Protect { ports: vec![3001], ..Default::default() };
+        app.toggle_subtree();
+        assert_eq!(app.checked_pids(), vec![30]);
+        app.tree_selected = 3;
+        app.toggle_checked();
+        assert_eq!(app.checked_pids(), vec![30]);
+        app.checked.iter_mut().for_each(|c| *c = false);
+        assert!(app.targets().is_empty());
+    }
+
+    #[test]
+    fn test_unit_kill_needs_confirmation() {
+        let mut app = make_app();
+        app.processes[0].unit = Some(crate::systemd::Unit { name: "dev-api.service".to_string(), user: true });
+        let targets = app.processes[..1].to_vec();
+        app.start_kills(targets);
+        assert!(app.kills.is_none());
+        assert_eq!(app.manager_confirm.as_ref().map(|(i, t)| (*i, t.len())), Some((0, 1)));
+
+        // Choosing "Kill PID" drops the unit so the process itself is killed.
+        app.move_manager_confirm(1);
+        let (_, ref targets) = *app.manager_confirm.as_ref().unwrap();
+        assert!(targets[0].unit.is_some());
+        app.confirm_manager_menu();
+        assert!(app.manager_confirm.is_none());
+        assert!(app.kills.as_ref().is_some_and(|b| b.targets[0].manager.is_none()));
+    }
+
+    #[test]
+    fn test_container_kill_needs_confirmation() {
+        let mut app = make_app();
+        app.processes[0].container = Some(crate::container::Container {
+            id: "3f2a".to_string(),
+            name: "web".to_string(),
+            image: Some("nginx".to_string()),
+            compose_project: Some("shop".to_string()),
+            runtime: crate::container::Runtime::Docker,
+            ports: vec![3000],
+            socket: Some("/var/run/docker.sock".into()),
+        });
+        let targets = app.processes[..1].to_vec();
+        app.start_kills(targets);
+        assert!(app.kills.is_none());
+        let (_, ref pending) = *app.manager_confirm.as_ref().unwrap();
+        assert_eq!(manager_view::choices(pending), [manager_view::ManagerChoice::Stop, manager_view::ManagerChoice::KillProcess]);
+        let command = Manager::of(&pending[0], None).map(|m| m.command());
+        assert_eq!(command.as_deref(), Some("docker stop web (nginx, compose project shop)"));
+
+        app.move_manager_confirm(1);
+        app.confirm_manager_menu();
+        assert!(app.kills.as_ref().is_some_and(|b| b.targets[0].manager.is_none()));
+    }
+
+    #[test]
+    fn test_pm2_app_kill_offers_stop_and_delete() {
+        use std::sync::Mutex;
+        static ACTIONS: Mutex<Vec<Pm2Action>> = Mutex::new(Vec::new());
+        fn recording_kill(p: &ProcessInfo, signal: Option<Signal>, pm2: Pm2Action, progress: &mut dyn FnMut(crate::killer::Stage)) -> Result<Option<HistoryEntry>, String> {
+            ACTIONS.lock().unwrap().push(pm2);
+            no_kill(p, signal, pm2, progress)
+        }
+
+        let mut app = make_app();
+        app.kill = recording_kill;
+        app.processes[0].supervisor = Some(crate::supervisor::Supervisor {
+            kind: crate::supervisor::Kind::Pm2,
+            pid: 9,
+            name: "PM2 v5.3.0: God Daemon".to_string(),
+            cmd: vec![],
+            cwd: None,
+            pm2_app: Some(crate::supervisor::Pm2App { name: "api".to_string(), id: 0 }),
+        });
+        let targets = app.processes[..1].to_vec();
+        app.start_kills(targets.clone());
+        let (_, ref pending) = *app.manager_confirm.as_ref().unwrap();
+        assert_eq!(
+            manager_view::choices(pending),
+            [manager_view::ManagerChoice::Stop, manager_view::ManagerChoice::Pm2Delete, manager_view::ManagerChoice::KillProcess]
+        );
+
+        // The default choice is `pm2 stop`; delete only when picked.
+        app.confirm_manager_menu();
+        wait_for_kills(&mut app);
+        app.start_kills(targets);
+        app.move_manager_confirm(1);
+        app.confirm_manager_menu();
+        wait_for_kills(&mut app);
+        assert_eq!(*ACTIONS.lock().unwrap(), [Pm2Action::Stop, Pm2Action::Delete]);
+    }
+
+    fn wait_for_kills(app: &mut AppState) {
+        while app.kills.as_ref().is_some_and(|b| !b.is_done()) {
+            app.poll_kills();
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn test_poll_restarts_shows_the_outcome_once_done() {
+        let mut app = make_app();
+        let (tx, rx) = std::sync::mpsc::channel();
+        app.restarts = Some(rx);
+        app.poll_restarts();
+        assert!(app.restarts.is_some());
+        tx.send(" Restarted node as PID 42".to_string()).unwrap();
+        app.poll_restarts();
+        assert!(app.restarts.is_none());
+        assert_eq!(app.status_message.as_deref(), Some(" Restarted node as PID 42"));
+    }
+
+    #[test]
+    fn test_tree_collapse_and_expand() {
+        let mut app = make_tree_app();
+        app.tree_selected = 1;
+        app.set_expanded(false);
+        assert_eq!(app.tree_rows().len(), 2);
+        app.set_expanded(true);
+        assert_eq!(app.tree_rows().len(), 4);
+    }
 }
@@ -0,0 +1,119 @@
+use ratatui::layout::{Position, Rect};
+use super::keys::{self, Action};
+use super::detail_tabs::DetailTab;
+use super::{AppState, SortColumn};
+
+/// Clickable regions recorded while drawing the current frame, used to hit-test mouse events.
+#[derive(Debug, Clone, Default)]
+pub struct HitMap {
+    /// Area holding the selectable rows and the index of the first visible row.
+    pub rows: Option<(Rect, usize)>,
+    /// Number of rows in the view; clicks on the blank space below the last one hit nothing.
+    pub row_count: usize,
+    /// Width of the checkbox column at the left edge of `rows`; 0 when there is none.
+    pub checkbox_width: u16,
+    pub headers: Vec<(Rect, SortColumn)>,
+    /// Footer / action bar entries and what clicking them does.
+    pub buttons: Vec<(Rect, Action)>,
+    /// Detail view tab titles.
+    pub tabs: Vec<(Rect, DetailTab)>,
+    /// Scrollable text (the detail panel); the mouse wheel scrolls it instead of moving the
+    /// selection.
+    pub scrollable: Option<Rect>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Target {
+    Row { index: usize, checkbox: bool },
+    Header(SortColumn),
+    Button(Action),
+    Tab(DetailTab),
+    Nothing,
+}
+
+impl HitMap {
+    pub fn hit(&self, column: u16, row: u16) -> Target {
+        let pos = Position::new(column, row);
+        if let Some(&(_, action)) = self.buttons.iter().find(|(r, _)| r.contains(pos)) {
+            return Target::Button(action);
+        }
+        if let Some(&(_, tab)) = self.tabs.iter().find(|(r, _)| r.contains(pos)) {
+            return Target::Tab(tab);
+        }
+        if let Some(&(_, col)) = self.headers.iter().find(|(r, _)| r.contains(pos)) {
+            return Target::Header(col);
+        }
+        if let Some((area, offset)) = self.rows {
+            let index = offset + row.saturating_sub(area.y) as usize;
+            if area.contains(pos) && index < self.row_count {
+                return Target::Row { index, checkbox: column < area.x + self.checkbox_width };
+            }
+        }
+        Target::Nothing
+    }
+}
+
+/// Lays out `items` (`("Enter", "kill", Action::KillSelected)` renders as `Enter=kill`) as a one-line
+/// action bar in `area`. Returns the text and the clickable rectangle of each entry.
+pub fn action_bar(area: Rect, items: &[(String, &str, Action)]) -> (String, Vec<(Rect, Action)>) {
+    let mut text = String::from(" ");
+    let mut buttons = Vec::new();
+    for (i, (key, label, action)) in items.iter().enumerate() {
+        if i > 0 {
+            text.push_str("  ");
+        }
+        let entry = format!("{}={}", key, label);
+        let x = area.x.saturating_add(text.chars().count() as u16);
+        let width = (entry.chars().count() as u16).min(area.right().saturating_sub(x));
+        buttons.push((Rect::new(x, area.y, width, 1), *action));
+        text.push_str(&entry);
+    }
+    (text, buttons)
+}
+
+/// Footer text for the current view: the status message if there is one, otherwise the
+/// view's clickable key hints.
+pub fn footer(area: Rect, state: &mut AppState) -> String {
+    if let Some(ref s) = state.status_message {
+        return s.clone();
+    }
+    let (text, buttons) = action_bar(area, &keys::footer(state));
+    state.hits.buttons = buttons;
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hit_rows_and_checkbox() {
+        let map = HitMap {
+            rows: Some((Rect::new(1, 2, 40, 5), 10)),
+            row_count: 14,
+            checkbox_width: 5,
+            ..Default::default()
+        };
+        assert_eq!(map.hit(3, 2), Target::Row { index: 10, checkbox: true });
+        assert_eq!(map.hit(20, 4), Target::Row { index: 12, checkbox: false });
+        assert_eq!(map.hit(20, 7), Target::Nothing);
+        // Below the last row, inside the area.
+        assert_eq!(map.hit(3, 6), Target::Nothing);
+    }
+
+    #[test]
+    fn test_action_bar_buttons() {
+        let area = Rect::new(0, 20, 80, 1);
+        let items = [
+            ("Space".to_string(), "select", Action::ToggleCheck),
+            ("Q".to_string(), "quit", Action::Quit),
+        ];
+        let (text, buttons) = action_bar(area, &items);
+        assert_eq!(text, " Space=select  Q=quit");
+        assert_eq!(buttons[0].0, Rect::new(1, 20, 12, 1));
+        assert_eq!(buttons[1].0, Rect::new(15, 20, 6, 1));
+        let map = HitMap { buttons, ..Default::default() };
+        assert_eq!(map.hit(16, 20), Target::Button(Action::Quit));
+        assert_eq!(map.hit(13, 20), Target::Nothing);
+    }
+}
@@ -0,0 +1,68 @@
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+use super::{keys, AppState};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PaneAction {
+    Jump,
+    Kill,
+}
+
+pub const ITEMS: [(PaneAction, &str, &str); 2] = [
+    (PaneAction::Jump, "Jump", "switch to the pane, or attach to its session after quitting"),
+    (PaneAction::Kill, "Close", "kill the pane; everything running in it gets SIGHUP"),
+];
+
+/// Pane menu: jump to or close the tmux pane / screen window of the current process.
+pub fn render(f: &mut Frame, area: Rect, state: &AppState) {
+    let Some(selected) = state.pane_menu else { return };
+    let Some(pane) = state.current_process().and_then(|p| p.pane.as_ref()) else { return };
+
+    let items: Vec<ListItem> = ITEMS
+        .iter()
+        .map(|(_, label, description)| {
+            ListItem::new(Line::from(vec![
+                Span::styled(format!(" {:<7}", label), state.theme.accent),
+                Span::styled(*description, state.theme.muted),
+            ]))
+        })
+        .collect();
+
+    let height = (items.len() as u16 + 2).min(area.height);
+    let [popup] = Layout::horizontal([Constraint::Length(76)]).flex(Flex::Center).areas(area);
+    let [popup] = Layout::vertical([Constraint::Length(height)]).flex(Flex::Center).areas(popup);
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(keys::menu_title(&state.keymap, &pane.display(), "run")))
+        .highlight_style(state.theme.selected);
+    let mut list_state = ListState::default().with_selected(Some(selected));
+    f.render_widget(Clear, popup);
+    f.render_stateful_widget(list, popup, &mut list_state);
+}
+
+/// Confirmation before closing a pane, listing the listening processes that go with it.
+pub fn render_close(f: &mut Frame, area: Rect, state: &AppState) {
+    let Some(ref pane) = state.pane_close else { return };
+
+    let mut lines = vec![Line::styled(" Everything running in it gets SIGHUP:", state.theme.muted)];
+    lines.extend(state.pane_close_processes().iter().map(|p| {
+        Line::from(vec![
+            Span::styled(format!(" {:<8}", p.pid), state.theme.accent),
+            Span::raw(format!("{} {}", p.name, p.ports_display())),
+        ])
+    }));
+
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let [popup] = Layout::horizontal([Constraint::Length(76)]).flex(Flex::Center).areas(area);
+    let [popup] = Layout::vertical([Constraint::Length(height)]).flex(Flex::Center).areas(popup);
+
+    let name = format!("Close {}?", pane.display());
+    let text = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(keys::menu_title(&state.keymap, &name, "close")));
+    f.render_widget(Clear, popup);
+    f.render_widget(text, popup);
+}
@@ -0,0 +1,113 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Style,
+    widgets::{Block, Borders, Cell, Row, Table, TableState, Paragraph},
+    Frame,
+};
+use crate::filter::FilterMode;
+use super::keys::{self, Action};
+use super::{mouse, AppState};
+
+pub fn render(f: &mut Frame, area: Rect, state: &mut AppState) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(5), Constraint::Length(2)])
+        .split(area);
+
+    render_table(f, chunks[0], state);
+    render_footer(f, chunks[1], state);
+}
+
+fn render_table(f: &mut Frame, area: Rect, state: &mut AppState) {
+    let mode_str = match state.filter_mode {
+        FilterMode::Strict => "Strict: dev runtimes only",
+        FilterMode::Loose => "Loose: all listening processes",
+    };
+    let hints = keys::title_hints(&state.keymap, &[(&[Action::SwitchView], "list view"), (&[Action::SwitchFilter], "switch filter")]);
+    let title = format!(" Ports  [{}]  {} ", mode_str, hints);
+
+    let header = Row::new(vec![
+        Cell::from("  "),
+        Cell::from("Port"),
+        Cell::from("Proto"),
+        Cell::from("Bind"),
+        Cell::from("Owners"),
+        Cell::from("Project"),
+    ])
+    .style(state.theme.header);
+
+    let port_rows = state.port_rows();
+    let rows: Vec<Row> = port_rows
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let owners: Vec<_> = state
+                .processes
+                .iter()
+                .filter(|p| row.pids.contains(&p.pid))
+                .collect();
+            let checkbox = if row.pids.iter().all(|&pid| state.is_checked(pid)) {
+                "[x]"
+            } else if row.pids.iter().any(|&pid| state.is_checked(pid)) {
+                "[-]"
+            } else {
+                "[ ]"
+            };
+            let owner_str = owners
+                .iter()
+                .map(|p| format!("{} {}", p.pid, p.name_display()))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let mut dirs: Vec<&str> = owners.iter().filter_map(|p| p.cwd.as_deref()).collect();
+            dirs.sort_unstable();
+            dirs.dedup();
+
+            let row_style = if i == state.port_selected {
+                state.theme.selected
+            } else {
+                Style::default()
+            };
+
+            Row::new(vec![
+                Cell::from(checkbox),
+                Cell::from(row.port.to_string()),
+                Cell::from(row.protocols.join("/")),
+                Cell::from(row.addresses.join(", ")),
+                Cell::from(owner_str),
+                Cell::from(dirs.join(", ")),
+            ])
+            .style(row_style)
+        })
+        .collect();
+
+    let row_count = rows.len();
+    let widths = [
+        Constraint::Length(5),
+        Constraint::Length(7),
+        Constraint::Length(9),
+        Constraint::Length(18),
+        Constraint::Length(28),
+        Constraint::Min(20),
+    ];
+
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title(title));
+
+    let selected = if port_rows.is_empty() { None } else { Some(state.port_selected) };
+    let mut table_state = TableState::default().with_selected(selected);
+    f.render_stateful_widget(table, area, &mut table_state);
+
+    // Inside the border: one header line, then the rows.
+    let inner = Block::default().borders(Borders::ALL).inner(area);
+    let rows_area = Rect { y: inner.y + 1, height: inner.height.saturating_sub(1), ..inner };
+    state.hits.rows = Some((rows_area, table_state.offset()));
+    state.hits.row_count = row_count;
+    state.hits.checkbox_width = 5; // the checkbox column
+}
+
+fn render_footer(f: &mut Frame, area: Rect, state: &mut AppState) {
+    let msg = mouse::footer(area, state);
+    let p = Paragraph::new(msg).style(state.theme.muted);
+    f.render_widget(p, area);
+}
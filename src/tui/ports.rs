@@ -0,0 +1,95 @@
+use crate::process_info::ProcessInfo;
+
+/// One listening port and every process holding it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PortRow {
+    pub port: u16,
+    /// `tcp` and/or `tcp6`.
+    pub protocols: Vec<&'static str>,
+    pub addresses: Vec<String>,
+    /// Owning PIDs; more than one for SO_REUSEPORT or pre-fork workers sharing a socket.
+    pub pids: Vec<u32>,
+}
+
+/// Builds one row per listening port of `processes`, sorted by port.
+pub fn build_rows(processes: &[ProcessInfo]) -> Vec<PortRow> {
+    let mut rows: Vec<PortRow> = Vec::new();
+    for p in processes {
+        for port in &p.ports {
+            let idx = match rows.iter().position(|r| r.port == *port) {
+                Some(idx) => idx,
+                None => {
+                    rows.push(PortRow {
+                        port: *port,
+                        protocols: Vec::new(),
+                        addresses: Vec::new(),
+                        pids: Vec::new(),
+                    });
+                    rows.len() - 1
+                }
+            };
+            let row = &mut rows[idx];
+            if !row.pids.contains(&p.pid) {
+                row.pids.push(p.pid);
+            }
+            for l in p.listeners.iter().filter(|l| l.port == *port) {
+                if !row.protocols.contains(&l.protocol()) {
+                    row.protocols.push(l.protocol());
+                }
+                let addr = l.address_display();
+                if !row.addresses.contains(&addr) {
+                    row.addresses.push(addr);
+                }
+            }
+        }
+    }
+    rows.sort_by_key(|r| r.port);
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process_info::Listener;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    fn make_proc(pid: u32, listeners: Vec<(IpAddr, u16)>) -> ProcessInfo {
+        let listeners: Vec<Listener> = listeners
+            .into_iter()
+            .map(|(address, port)| Listener { address, port })
+            .collect();
+        let mut ports: Vec<u16> = listeners.iter().map(|l| l.port).collect();
+        ports.dedup();
+        ProcessInfo { pid, name: "node".to_string(), ports, listeners, ..Default::default() }
+    }
+
+    #[test]
+    fn test_build_rows_one_per_port() {
+        let v4 = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let v6 = IpAddr::V6(Ipv6Addr::UNSPECIFIED);
+        let procs = vec![
+            make_proc(1, vec![(v4, 5173), (v6, 5173)]),
+            make_proc(2, vec![(v4, 3000)]),
+        ];
+        let rows = build_rows(&procs);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].port, 3000);
+        assert_eq!(rows[1].protocols, vec!["tcp", "tcp6"]);
+        assert_eq!(rows[1].addresses, vec!["127.0.0.1", "*"]);
+        assert_eq!(rows[1].pids, vec![1]);
+    }
+
+    #[test]
+    fn test_build_rows_shared_port() {
+        let any = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
+        let procs = vec![
+            make_proc(10, vec![(any, 8080)]),
+            make_proc(11, vec![(any, 8080)]),
+            make_proc(12, vec![(any, 8080)]),
+        ];
+        let rows = build_rows(&procs);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].pids, vec![10, 11, 12]);
+        assert_eq!(rows[0].addresses, vec!["*"]);
+    }
+}
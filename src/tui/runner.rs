@@ -1,13 +1,21 @@
 use std::io;
+use std::sync::mpsc;
+use std::thread;
 use std::time::Duration;
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, MouseButton,
+        MouseEvent, MouseEventKind,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use ratatui::{backend::CrosstermBackend, Terminal};
-use crate::killer;
-use super::{AppState, View, list_view, detail_view};
+use ratatui::{backend::CrosstermBackend, layout::Position, Terminal};
+use crate::restart;
+use super::keys::Action;
+use super::metrics::Sampler;
+use super::mouse::{HitMap, Target};
+use super::{AppState, View, list_view, detail_view, tree_view, port_view, help_view, kill_view, signal_view, manager_view, command_view, yank_view, pane_view, clipboard};
 
 pub fn run(mut state: AppState) -> io::Result<()> {
     enable_raw_mode()?;
@@ -26,6 +34,20 @@ pub fn run(mut state: AppState) -> io::Result<()> {
     )?;
     terminal.show_cursor()?;
 
+    if let Some(pane) = state.attach.take() {
+        println!("Attaching to {}...", pane.display());
+        if let Err(e) = pane.attach() {
+            eprintln!("Failed to attach to {}: {}", pane.display(), e);
+        }
+    }
+
+    if !state.yanked.is_empty() {
+        println!("Copied to the clipboard this session (if your terminal ignored OSC 52):");
+        for (label, value) in &state.yanked {
+            println!("  {:<10} {}", format!("{}:", label), value);
+        }
+    }
+
     result
 }
 
@@ -33,86 +55,341 @@ fn run_loop<B: ratatui::backend::Backend>(
     terminal: &mut Terminal<B>,
     state: &mut AppState,
 ) -> io::Result<()> {
-    loop {
+    let mut sampler = Sampler::default();
+    while !state.should_quit {
+        if sampler.due() {
+            let samples = sampler.sample(&state.all_processes);
+            state.record_samples(&samples);
+            if let Some(ref mut inspection) = state.inspection {
+                inspection.invalidate();
+            }
+        }
+        state.poll_kills();
+        state.poll_restarts();
+        state.load_detail();
+        state.hits = HitMap::default();
         terminal.draw(|f| {
             let area = f.area();
             match state.view {
                 View::List => list_view::render(f, area, state),
                 View::Detail => detail_view::render(f, area, state),
+                View::Tree => tree_view::render(f, area, state),
+                View::Ports => port_view::render(f, area, state),
+            }
+            if state.show_help {
+                help_view::render(f, area, state);
+            }
+            if state.command_inspector.is_some() {
+                command_view::render(f, area, state);
+            }
+            if state.yank_menu.is_some() {
+                yank_view::render(f, area, state);
+            }
+            if state.pane_menu.is_some() {
+                pane_view::render(f, area, state);
+            }
+            if state.pane_close.is_some() {
+                pane_view::render_close(f, area, state);
+            }
+            if state.signal_menu.is_some() {
+                signal_view::render(f, area, state);
+            }
+            if state.manager_confirm.is_some() {
+                manager_view::render(f, area, state);
+            }
+            if state.kill_panel_visible() {
+                kill_view::render(f, area, state);
             }
         })?;
 
         if event::poll(Duration::from_millis(50))? {
-            if let Event::Key(key) = event::read()? {
-                if key.kind != KeyEventKind::Press {
-                    continue;
+            match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => {
+                    state.status_message = None;
+                    handle_key(state, key.code);
                 }
-                state.status_message = None;
+                Event::Mouse(mouse) => handle_mouse(state, mouse),
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
 
-                match key.code {
-                    KeyCode::Char('q') | KeyCode::Esc => {
-                        state.should_quit = true;
-                        break;
-                    }
-                    KeyCode::Tab => state.switch_view(),
-                    KeyCode::Up | KeyCode::Char('k') => state.move_up(),
-                    KeyCode::Down | KeyCode::Char('j') => state.move_down(),
-                    KeyCode::Char(' ') => state.toggle_checked(),
-                    KeyCode::Char('a') | KeyCode::Char('A') => state.select_all(),
-                    KeyCode::Char('f') | KeyCode::Char('F') => {
-                        use crate::filter::FilterMode;
-                        state.filter_mode = match state.filter_mode {
-                            FilterMode::Strict => FilterMode::Loose,
-                            FilterMode::Loose => FilterMode::Strict,
-                        };
-                        state.refilter();
-                    }
-                    KeyCode::Enter => {
-                        let pids = state.checked_pids();
-                        if pids.is_empty() {
-                            if state.view == View::Detail {
-                                if let Some(p) = state.current_process() {
-                                    let pid = p.pid;
-                                    handle_kill(state, pid);
-                                }
-                            } else {
-                                state.status_message = Some(" No processes selected (use Space to check)".to_string());
-                            }
-                        } else {
-                            let mut errors = vec![];
-                            let mut killed_pids = vec![];
-                            for pid in pids {
-                                match killer::kill(pid) {
-                                    Ok(_) => killed_pids.push(pid),
-                                    Err(e) => errors.push(e.to_string()),
-                                }
-                            }
-                            if !killed_pids.is_empty() {
-                                state.remove_processes(&killed_pids);
-                            }
-                            if errors.is_empty() {
-                                state.status_message = Some(" Killed selected processes.".to_string());
-                            } else {
-                                state.status_message = Some(format!(" Errors: {}", errors.join("; ")));
-                            }
-                        }
+/// Lines moved by PageUp / PageDown in the detail panel and the command inspector.
+const PAGE: i16 = 10;
+
+fn handle_key(state: &mut AppState, code: KeyCode) {
+    if state.kill_panel_visible() {
+        // The results panel stays up until every kill has finished; then any key closes it.
+        state.dismiss_kills();
+        return;
+    }
+    if state.manager_confirm.is_some() {
+        match state.keymap.action_for(state.view, code) {
+            Some(Action::MoveUp) => state.move_manager_confirm(-1),
+            Some(Action::MoveDown) => state.move_manager_confirm(1),
+            Some(Action::KillSelected) => state.confirm_manager_menu(),
+            Some(Action::Quit) => state.manager_confirm = None,
+            _ => {}
+        }
+        return;
+    }
+    if state.signal_menu.is_some() {
+        // The menu reuses the move, kill and quit keys to pick, send and close.
+        match state.keymap.action_for(state.view, code) {
+            Some(Action::MoveUp) => state.move_signal_menu(-1),
+            Some(Action::MoveDown) => state.move_signal_menu(1),
+            Some(Action::KillSelected) => state.confirm_signal_menu(),
+            Some(Action::Quit | Action::Signal) => state.signal_menu = None,
+            _ => {}
+        }
+        return;
+    }
+    if state.yank_menu.is_some() {
+        match state.keymap.action_for(state.view, code) {
+            Some(Action::MoveUp) => state.move_yank_menu(-1),
+            Some(Action::MoveDown) => state.move_yank_menu(1),
+            Some(Action::KillSelected) => yank(state),
+            Some(Action::Quit | Action::Yank) => state.yank_menu = None,
+            _ => {}
+        }
+        return;
+    }
+    if state.pane_close.is_some() {
+        match state.keymap.action_for(state.view, code) {
+            Some(Action::KillSelected) => close_pane(state),
+            Some(Action::Quit) => state.pane_close = None,
+            _ => {}
+        }
+        return;
+    }
+    if state.pane_menu.is_some() {
+        match state.keymap.action_for(state.view, code) {
+            Some(Action::MoveUp) => state.move_pane_menu(-1),
+            Some(Action::MoveDown) => state.move_pane_menu(1),
+            Some(Action::KillSelected) => run_pane_action(state),
+            Some(Action::Quit | Action::Pane) => state.pane_menu = None,
+            _ => {}
+        }
+        return;
+    }
+    if state.command_inspector.is_some() {
+        match state.keymap.action_for(state.view, code) {
+            Some(Action::MoveUp) => state.scroll(-1),
+            Some(Action::MoveDown) => state.scroll(1),
+            Some(Action::ScrollUp) => state.scroll(-PAGE),
+            Some(Action::ScrollDown) => state.scroll(PAGE),
+            Some(Action::Quit | Action::InspectCommand) => state.command_inspector = None,
+            _ => {}
+        }
+        return;
+    }
+    if state.show_help {
+        // Any of the help / quit keys closes the overlay; everything else is ignored.
+        if matches!(state.keymap.action_for(state.view, code), Some(Action::Help | Action::Quit)) {
+            state.show_help = false;
+        }
+        return;
+    }
+    if let Some(action) = state.keymap.action_for(state.view, code) {
+        perform(state, action);
+    }
+}
+
+fn perform(state: &mut AppState, action: Action) {
+    if !action.available(state) {
+        return;
+    }
+    match action {
+        Action::Quit => state.should_quit = true,
+        Action::Help => state.show_help = true,
+        Action::SwitchView => state.switch_view(),
+        Action::MoveUp => state.move_up(),
+        Action::MoveDown => state.move_down(),
+        Action::Collapse => state.set_expanded(false),
+        Action::Expand => state.set_expanded(true),
+        Action::ToggleGroup => state.toggle_grouped(),
+        Action::NextTab => state.cycle_detail_tab(1),
+        Action::PrevTab => state.cycle_detail_tab(-1),
+        Action::ScrollUp => state.scroll(-PAGE),
+        Action::ScrollDown => state.scroll(PAGE),
+        Action::InspectCommand => state.open_command_inspector(),
+        Action::Yank => state.open_yank_menu(),
+        Action::Pane => state.open_pane_menu(),
+        Action::SelectSubtree => state.toggle_subtree(),
+        Action::ToggleCheck => state.toggle_checked(),
+        Action::SelectAll => state.select_all(),
+        Action::SwitchFilter => {
+            use crate::filter::FilterMode;
+            state.filter_mode = match state.filter_mode {
+                FilterMode::Strict => FilterMode::Loose,
+                FilterMode::Loose => FilterMode::Strict,
+            };
+            state.refilter();
+        }
+        Action::KillSelected => {
+            let targets = state.targets();
+            state.start_kills(targets);
+        }
+        Action::Signal => state.open_signal_menu(),
+        Action::Undo => handle_undo(state),
+    }
+}
+
+/// Wheel scrolls, a click selects a row (and toggles it on the checkbox column), sorts by a
+/// header or presses a footer action.
+fn handle_mouse(state: &mut AppState, mouse: MouseEvent) {
+    if state.kill_panel_visible() {
+        if let MouseEventKind::Down(_) = mouse.kind {
+            state.dismiss_kills();
+        }
+        return;
+    }
+    if state.manager_confirm.is_some() {
+        match mouse.kind {
+            MouseEventKind::ScrollUp => state.move_manager_confirm(-1),
+            MouseEventKind::ScrollDown => state.move_manager_confirm(1),
+            MouseEventKind::Down(_) => state.manager_confirm = None,
+            _ => {}
+        }
+        return;
+    }
+    if state.signal_menu.is_some() {
+        match mouse.kind {
+            MouseEventKind::ScrollUp => state.move_signal_menu(-1),
+            MouseEventKind::ScrollDown => state.move_signal_menu(1),
+            MouseEventKind::Down(_) => state.signal_menu = None,
+            _ => {}
+        }
+        return;
+    }
+    if state.yank_menu.is_some() {
+        match mouse.kind {
+            MouseEventKind::ScrollUp => state.move_yank_menu(-1),
+            MouseEventKind::ScrollDown => state.move_yank_menu(1),
+            MouseEventKind::Down(_) => state.yank_menu = None,
+            _ => {}
+        }
+        return;
+    }
+    if state.pane_close.is_some() {
+        if let MouseEventKind::Down(_) = mouse.kind {
+            state.pane_close = None;
+        }
+        return;
+    }
+    if state.pane_menu.is_some() {
+        match mouse.kind {
+            MouseEventKind::ScrollUp => state.move_pane_menu(-1),
+            MouseEventKind::ScrollDown => state.move_pane_menu(1),
+            MouseEventKind::Down(_) => state.pane_menu = None,
+            _ => {}
+        }
+        return;
+    }
+    if state.command_inspector.is_some() {
+        match mouse.kind {
+            MouseEventKind::ScrollUp => state.scroll(-3),
+            MouseEventKind::ScrollDown => state.scroll(3),
+            MouseEventKind::Down(_) => state.command_inspector = None,
+            _ => {}
+        }
+        return;
+    }
+    if state.show_help {
+        if let MouseEventKind::Down(_) = mouse.kind {
+            state.show_help = false;
+        }
+        return;
+    }
+    let over_scrollable = state
+        .hits
+        .scrollable
+        .is_some_and(|r| r.contains(Position::new(mouse.column, mouse.row)));
+    match mouse.kind {
+        MouseEventKind::ScrollUp if over_scrollable => state.scroll(-3),
+        MouseEventKind::ScrollDown if over_scrollable => state.scroll(3),
+        MouseEventKind::ScrollUp => state.move_up(),
+        MouseEventKind::ScrollDown => state.move_down(),
+        MouseEventKind::Down(MouseButton::Left) => {
+            match state.hits.hit(mouse.column, mouse.row) {
+                Target::Row { index, checkbox } => {
+                    state.select_row(index);
+                    if checkbox {
+                        state.toggle_checked();
                     }
-                    _ => {}
                 }
+                Target::Header(column) => state.set_sort(column),
+                Target::Tab(tab) => state.set_detail_tab(tab),
+                Target::Button(action) => {
+                    state.status_message = None;
+                    perform(state, action);
+                }
+                Target::Nothing => {}
             }
         }
+        _ => {}
     }
-    Ok(())
 }
 
-fn handle_kill(state: &mut AppState, pid: u32) {
-    match killer::kill(pid) {
-        Ok(_) => {
-            state.remove_processes(&[pid]);
-            state.status_message = Some(format!(" Killed PID {}.", pid));
-        }
-        Err(e) => {
-            state.status_message = Some(format!(" {}", e));
+/// Copies the field chosen in the yank menu.
+fn yank(state: &mut AppState) {
+    if let Some(value) = state.confirm_yank_menu() {
+        if let Err(e) = clipboard::copy(&value) {
+            state.status_message = Some(format!(" Failed to copy: {}", e));
         }
     }
 }
+
+/// Runs the action chosen in the pane menu. Outside the pane's tmux server or screen session,
+/// jumping quits and attaches once the terminal is restored; Close first asks to confirm.
+fn run_pane_action(state: &mut AppState) {
+    let Some(pane) = state.confirm_pane_menu() else { return };
+    if !pane.is_local() {
+        state.attach = Some(pane);
+        state.should_quit = true;
+        return;
+    }
+    let result = pane.switch_to().map(|_| format!(" Switched to {}", pane.display()));
+    state.status_message = Some(result.unwrap_or_else(|e| format!(" {} failed: {}", pane.display(), e)));
+}
+
+/// Closes the pane confirmed in the Close prompt.
+fn close_pane(state: &mut AppState) {
+    let Some(pane) = state.pane_close.take() else { return };
+    let result = pane.kill().map(|_| format!(" Closed {}", pane.display()));
+    state.status_message = Some(result.unwrap_or_else(|e| format!(" Closing {} failed: {}", pane.display(), e)));
+}
+
+/// Relaunches everything killed by the most recent kill action, in the background: each
+/// relaunch may wait seconds for the old process to free its ports.
+fn handle_undo(state: &mut AppState) {
+    if state.restarts.is_some() {
+        state.status_message = Some(" A restart is still in progress.".to_string());
+        return;
+    }
+    if state.last_killed.is_empty() {
+        state.status_message = Some(" Nothing to undo.".to_string());
+        return;
+    }
+    let entries = std::mem::take(&mut state.last_killed);
+    state.status_message = Some(format!(" Restarting {} process(es)...", entries.len()));
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut restarted = vec![];
+        let mut errors = vec![];
+        for entry in entries {
+            match restart::relaunch(&entry) {
+                Ok((pid, log)) => restarted.push(format!("{} as PID {} (log: {})", entry.name, pid, log.display())),
+                Err(e) => errors.push(format!("{}: {}", entry.name, e)),
+            }
+        }
+        let _ = tx.send(if errors.is_empty() {
+            format!(" Restarted {}", restarted.join("; "))
+        } else {
+            format!(" Restart errors: {}", errors.join("; "))
+        });
+    });
+    state.restarts = Some(rx);
+}
@@ -0,0 +1,34 @@
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState},
+    Frame,
+};
+use crate::killer::Signal;
+use super::{keys, AppState};
+
+/// Signal menu: pick a signal for the processes the kill action would target.
+pub fn render(f: &mut Frame, area: Rect, state: &AppState) {
+    let Some(selected) = state.signal_menu else { return };
+
+    let items: Vec<ListItem> = Signal::ALL
+        .iter()
+        .map(|s| {
+            ListItem::new(Line::from(vec![
+                Span::styled(format!(" {:<9}", s.name()), state.theme.accent),
+                Span::raw(s.description()),
+            ]))
+        })
+        .collect();
+
+    let height = (items.len() as u16 + 2).min(area.height);
+    let [popup] = Layout::horizontal([Constraint::Length(60)]).flex(Flex::Center).areas(area);
+    let [popup] = Layout::vertical([Constraint::Length(height)]).flex(Flex::Center).areas(popup);
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(keys::menu_title(&state.keymap, "Send signal", "send")))
+        .highlight_style(state.theme.selected);
+    let mut list_state = ListState::default().with_selected(Some(selected));
+    f.render_widget(Clear, popup);
+    f.render_stateful_widget(list, popup, &mut list_state);
+}
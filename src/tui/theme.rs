@@ -0,0 +1,264 @@
+use std::str::FromStr;
+use ratatui::style::{Color, Modifier, Style};
+use crate::config::ThemeConfig;
+use crate::filter::ScoreLevel;
+use crate::process_info::ProcessInfo;
+
+/// Every style the views use, so no view hardcodes a color.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    /// Table headers and help headings.
+    pub header: Style,
+    /// The row under the cursor.
+    pub selected: Style,
+    /// Footers, tree branches and non-listening helpers.
+    pub muted: Style,
+    /// Listed processes in the tree view and key names in the help overlay.
+    pub accent: Style,
+    /// Project group headers.
+    pub group: Style,
+    pub score_high: Style,
+    pub score_medium: Style,
+    pub score_low: Style,
+    /// Processes matched by the `[protect]` config section.
+    pub protected: Style,
+    /// Processes owned by root or another system account.
+    pub system: Style,
+    /// Failed kills in the results panel.
+    pub error: Style,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ThemeError {
+    UnknownTheme(String),
+    BadColor { field: &'static str, value: String },
+}
+
+impl std::fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ThemeError::UnknownTheme(name) => write!(
+                f,
+                "unknown theme `{}` (expected dark, light, high-contrast or monochrome)",
+                name
+            ),
+            ThemeError::BadColor { field, value } => write!(
+                f,
+                "invalid color `{}` for theme.{} (use a name such as `blue`, an index 0-255 or `#rrggbb`)",
+                value, field
+            ),
+        }
+    }
+}
+
+fn fg(color: Color) -> Style {
+    Style::default().fg(color)
+}
+
+fn bold(style: Style) -> Style {
+    style.add_modifier(Modifier::BOLD)
+}
+
+impl Theme {
+    /// Default for dark terminals. Scores use blue / orange rather than green / red so they
+    /// stay distinguishable with red-green color blindness.
+    pub fn dark() -> Self {
+        Self {
+            header: bold(fg(Color::Yellow)),
+            selected: bold(Style::default().fg(Color::Black).bg(Color::LightCyan)),
+            muted: fg(Color::DarkGray),
+            accent: bold(fg(Color::LightBlue)),
+            group: bold(fg(Color::Cyan)),
+            score_high: fg(Color::LightBlue),
+            score_medium: fg(Color::Rgb(230, 159, 0)),
+            score_low: fg(Color::Gray),
+            protected: bold(fg(Color::Magenta)),
+            system: fg(Color::LightRed),
+            error: bold(fg(Color::LightRed)),
+        }
+    }
+
+    pub fn light() -> Self {
+        Self {
+            header: bold(fg(Color::Blue)),
+            selected: bold(Style::default().fg(Color::White).bg(Color::Blue)),
+            muted: fg(Color::DarkGray),
+            accent: bold(fg(Color::Blue)),
+            group: bold(fg(Color::Magenta)),
+            score_high: fg(Color::Blue),
+            score_medium: fg(Color::Rgb(176, 96, 0)),
+            score_low: fg(Color::DarkGray),
+            protected: bold(fg(Color::Magenta)),
+            system: fg(Color::Red),
+            error: bold(fg(Color::Red)),
+        }
+    }
+
+    pub fn high_contrast() -> Self {
+        Self {
+            header: bold(fg(Color::White)).add_modifier(Modifier::UNDERLINED),
+            selected: bold(Style::default().fg(Color::Black).bg(Color::Yellow)),
+            muted: fg(Color::White),
+            accent: bold(fg(Color::LightCyan)),
+            group: bold(fg(Color::White)),
+            score_high: bold(fg(Color::LightCyan)),
+            score_medium: bold(fg(Color::Yellow)),
+            score_low: fg(Color::White),
+            protected: bold(fg(Color::LightMagenta)),
+            system: bold(fg(Color::LightRed)),
+            error: bold(fg(Color::LightRed)).add_modifier(Modifier::UNDERLINED),
+        }
+    }
+
+    /// No colors at all, only text attributes; used for `NO_COLOR`.
+    pub fn monochrome() -> Self {
+        let plain = Style::default();
+        Self {
+            header: bold(plain),
+            selected: plain.add_modifier(Modifier::REVERSED),
+            muted: plain,
+            accent: bold(plain),
+            group: bold(plain),
+            score_high: bold(plain),
+            score_medium: plain,
+            score_low: plain.add_modifier(Modifier::DIM),
+            protected: plain.add_modifier(Modifier::UNDERLINED),
+            system: plain.add_modifier(Modifier::ITALIC),
+            error: bold(plain).add_modifier(Modifier::UNDERLINED),
+        }
+    }
+
+    pub fn by_name(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "high-contrast" => Some(Self::high_contrast()),
+            "monochrome" => Some(Self::monochrome()),
+            _ => None,
+        }
+    }
+
+    /// Builds the theme from the `[theme]` config section. `no_color` (the `NO_COLOR`
+    /// convention) wins over any configured theme or color and selects monochrome; the section
+    /// is still validated so mistakes surface either way.
+    pub fn from_config(config: &ThemeConfig, no_color: bool) -> Result<Self, ThemeError> {
+        let mut theme = match config.name.as_deref() {
+            Some(name) => Self::by_name(name).ok_or_else(|| ThemeError::UnknownTheme(name.to_string()))?,
+            None => Self::dark(),
+        };
+        let overrides: [(&'static str, &Option<String>, &mut Style); 11] = [
+            ("header", &config.header, &mut theme.header),
+            ("selected", &config.selected, &mut theme.selected),
+            ("muted", &config.muted, &mut theme.muted),
+            ("accent", &config.accent, &mut theme.accent),
+            ("group", &config.group, &mut theme.group),
+            ("score_high", &config.score_high, &mut theme.score_high),
+            ("score_medium", &config.score_medium, &mut theme.score_medium),
+            ("score_low", &config.score_low, &mut theme.score_low),
+            ("protected", &config.protected, &mut theme.protected),
+            ("system", &config.system, &mut theme.system),
+            ("error", &config.error, &mut theme.error),
+        ];
+        for (field, value, style) in overrides {
+            let Some(value) = value else { continue };
+            let color = Color::from_str(value)
+                .map_err(|_| ThemeError::BadColor { field, value: value.clone() })?;
+            // The selection is a background highlight; everything else is text color.
+            *style = if field == "selected" { style.bg(color) } else { style.fg(color) };
+        }
+        if no_color {
+            return Ok(Self::monochrome());
+        }
+        Ok(theme)
+    }
+
+    pub fn score(&self, score: u8) -> Style {
+        match ScoreLevel::of(score) {
+            ScoreLevel::High => self.score_high,
+            ScoreLevel::Medium => self.score_medium,
+            ScoreLevel::Low => self.score_low,
+        }
+    }
+
+    /// Row style for a process: protected wins over system; ordinary processes are unstyled.
+    pub fn process(&self, p: &ProcessInfo, protected: bool) -> Style {
+        if protected {
+            self.protected
+        } else if p.is_system() {
+            self.system
+        } else {
+            Style::default()
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+/// True when the `NO_COLOR` environment variable is set to a non-empty value.
+pub fn no_color() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_color_selects_monochrome() {
+        let cfg = ThemeConfig::default();
+        assert_eq!(Theme::from_config(&cfg, true).unwrap(), Theme::monochrome());
+        assert_eq!(Theme::from_config(&cfg, false).unwrap(), Theme::dark());
+        let explicit = ThemeConfig {
+            name: Some("light".to_string()),
+            score_high: Some("green".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(Theme::from_config(&explicit, true).unwrap(), Theme::monochrome());
+        assert_eq!(Theme::from_config(&explicit, false).unwrap().score_high.fg, Some(Color::Green));
+        let bad = ThemeConfig { name: Some("neon".to_string()), ..Default::default() };
+        assert!(Theme::from_config(&bad, true).is_err());
+    }
+
+    #[test]
+    fn test_monochrome_has_no_colors() {
+        let t = Theme::monochrome();
+        for style in [t.header, t.selected, t.muted, t.accent, t.group, t.score_high, t.score_medium, t.score_low, t.protected, t.system, t.error] {
+            assert_eq!(style.fg, None);
+            assert_eq!(style.bg, None);
+        }
+    }
+
+    #[test]
+    fn test_color_overrides() {
+        let cfg = ThemeConfig {
+            selected: Some("#112233".to_string()),
+            score_high: Some("green".to_string()),
+            ..Default::default()
+        };
+        let t = Theme::from_config(&cfg, false).unwrap();
+        assert_eq!(t.selected.bg, Some(Color::Rgb(0x11, 0x22, 0x33)));
+        assert_eq!(t.score_high.fg, Some(Color::Green));
+
+        let bad = ThemeConfig { muted: Some("blurple".to_string()), ..Default::default() };
+        assert_eq!(
+            Theme::from_config(&bad, false).unwrap_err(),
+            ThemeError::BadColor { field: "muted", value: "blurple".to_string() }
+        );
+        let unknown = ThemeConfig { name: Some("solarized".to_string()), ..Default::default() };
+        assert!(matches!(Theme::from_config(&unknown, false), Err(ThemeError::UnknownTheme(_))));
+    }
+
+    #[test]
+    fn test_process_style_priority() {
+        let t = Theme::dark();
+        let mut p = ProcessInfo { uid: Some(0), ..Default::default() };
+        assert_eq!(t.process(&p, true), t.protected);
+        assert_eq!(t.process(&p, false), t.system);
+        p.uid = Some(1000);
+        assert_eq!(t.process(&p, false), Style::default());
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::{HashMap, HashSet};
+use crate::process_info::ProcessInfo;
+
+/// One visible line of the process tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeRow {
+    pub pid: u32,
+    pub depth: usize,
+    /// Box-drawing prefix such as `│  └─ `.
+    pub prefix: String,
+    /// In the filtered list (i.e. a listener that can be checked), as opposed to a helper.
+    pub listed: bool,
+    pub has_children: bool,
+    pub expanded: bool,
+}
+
+/// Builds the tree of listed processes together with their ancestors and descendants.
+/// Ancestry stops below PID 1 so orphans show up as roots. Children of `collapsed` PIDs are
+/// hidden.
+pub fn build_rows(
+    all: &[ProcessInfo],
+    listed: &[ProcessInfo],
+    collapsed: &HashSet<u32>,
+) -> Vec<TreeRow> {
+    let by_pid: HashMap<u32, &ProcessInfo> = all.iter().map(|p| (p.pid, p)).collect();
+    let listed_pids: HashSet<u32> = listed.iter().map(|p| p.pid).collect();
+
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    for p in all {
+        if let Some(ppid) = p.parent_pid {
+            if ppid != p.pid {
+                children.entry(ppid).or_default().push(p.pid);
+            }
+        }
+    }
+    for kids in children.values_mut() {
+        kids.sort_unstable();
+    }
+
+    let mut relevant: HashSet<u32> = HashSet::new();
+    for &pid in &listed_pids {
+        // Ancestors
+        let mut cur = Some(pid);
+        while let Some(c) = cur {
+            if c <= 1 || !relevant.insert(c) {
+                break;
+            }
+            cur = by_pid.get(&c).and_then(|p| p.parent_pid);
+        }
+        // Descendants
+        let mut stack = children.get(&pid).cloned().unwrap_or_default();
+        while let Some(c) = stack.pop() {
+            if relevant.insert(c) {
+                stack.extend(children.get(&c).into_iter().flatten());
+            }
+        }
+    }
+
+    let mut roots: Vec<u32> = relevant
+        .iter()
+        .copied()
+        .filter(|pid| {
+            by_pid
+                .get(pid)
+                .and_then(|p| p.parent_pid)
+                .is_none_or(|ppid| !relevant.contains(&ppid))
+        })
+        .collect();
+    roots.sort_unstable();
+
+    let mut rows = Vec::new();
+    let count = roots.len();
+    for (i, root) in roots.into_iter().enumerate() {
+        push_subtree(root, 0, "", i + 1 == count, &children, &relevant, &listed_pids, collapsed, &mut rows);
+    }
+    rows
+}
+
+#[allow(clippy::too_many_arguments)]
+fn push_subtree(
+    pid: u32,
+    depth: usize,
+    indent: &str,
+    is_last: bool,
+    children: &HashMap<u32, Vec<u32>>,
+    relevant: &HashSet<u32>,
+    listed: &HashSet<u32>,
+    collapsed: &HashSet<u32>,
+    rows: &mut Vec<TreeRow>,
+) {
+    let kids: Vec<u32> = children
+        .get(&pid)
+        .map(|k| k.iter().copied().filter(|c| relevant.contains(c)).collect())
+        .unwrap_or_default();
+    let expanded = !collapsed.contains(&pid);
+    let prefix = if depth == 0 {
+        String::new()
+    } else {
+        format!("{}{}", indent, if is_last { "└─ " } else { "├─ " })
+    };
+    rows.push(TreeRow {
+        pid,
+        depth,
+        prefix,
+        listed: listed.contains(&pid),
+        has_children: !kids.is_empty(),
+        expanded,
+    });
+    if !expanded {
+        return;
+    }
+    let child_indent = if depth == 0 {
+        String::new()
+    } else {
+        format!("{}{}", indent, if is_last { "   " } else { "│  " })
+    };
+    let count = kids.len();
+    for (i, kid) in kids.into_iter().enumerate() {
+        push_subtree(kid, depth + 1, &child_indent, i + 1 == count, children, relevant, listed, collapsed, rows);
+    }
+}
+
+/// PIDs of the row at `index` and every row below it that is deeper, i.e. its visible subtree.
+pub fn subtree_pids(rows: &[TreeRow], index: usize) -> Vec<u32> {
+    let Some(root) = rows.get(index) else {
+        return Vec::new();
+    };
+    std::iter::once(root.pid)
+        .chain(rows[index + 1..].iter().take_while(|r| r.depth > root.depth).map(|r| r.pid))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_proc(pid: u32, parent: Option<u32>, name: &str, ports: Vec<u16>) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            name: name.to_string(),
+            ports,
+            parent_pid: parent,
+            ..Default::default()
+        }
+    }
+
+    // 1 init
+    // └─ 10 zsh
+    //    ├─ 20 npm
+    //    │  └─ 30 node :3000
+    //    │     └─ 40 esbuild
+    //    └─ 25 vim
+    // └─ 50 python :8000 (orphan)
+    fn sample() -> Vec<ProcessInfo> {
+        vec![
+            make_proc(1, None, "init", vec![]),
+            make_proc(10, Some(1), "zsh", vec![]),
+            make_proc(20, Some(10), "npm", vec![]),
+            make_proc(25, Some(10), "vim", vec![]),
+            make_proc(30, Some(20), "node", vec![3000]),
+            make_proc(40, Some(30), "esbuild", vec![]),
+            make_proc(50, Some(1), "python", vec![8000]),
+        ]
+    }
+
+    fn listed(all: &[ProcessInfo]) -> Vec<ProcessInfo> {
+        all.iter().filter(|p| !p.ports.is_empty()).cloned().collect()
+    }
+
+    #[test]
+    fn test_build_rows_ancestors_and_descendants() {
+        let all = sample();
+        let rows = build_rows(&all, &listed(&all), &HashSet::new());
+        let pids: Vec<u32> = rows.iter().map(|r| r.pid).collect();
+        assert_eq!(pids, vec![10, 20, 30, 40, 50]);
+        let depths: Vec<usize> = rows.iter().map(|r| r.depth).collect();
+        assert_eq!(depths, vec![0, 1, 2, 3, 0]);
+        assert!(rows[2].listed);
+        assert!(!rows[1].listed);
+        assert!(rows[4].listed && !rows[4].has_children);
+    }
+
+    #[test]
+    fn test_build_rows_prefixes() {
+        let all = sample();
+        let rows = build_rows(&all, &listed(&all), &HashSet::new());
+        assert_eq!(rows[1].prefix, "└─ ");
+        assert_eq!(rows[2].prefix, "   └─ ");
+        assert_eq!(rows[3].prefix, "      └─ ");
+    }
+
+    #[test]
+    fn test_collapsed_hides_children() {
+        let all = sample();
+        let collapsed: HashSet<u32> = [20].into_iter().collect();
+        let rows = build_rows(&all, &listed(&all), &collapsed);
+        let pids: Vec<u32> = rows.iter().map(|r| r.pid).collect();
+        assert_eq!(pids, vec![10, 20, 50]);
+        assert!(!rows[1].expanded && rows[1].has_children);
+    }
+
+    #[test]
+    fn test_subtree_pids() {
+        let all = sample();
+        let rows = build_rows(&all, &listed(&all), &HashSet::new());
+        assert_eq!(subtree_pids(&rows, 1), vec![20, 30, 40]);
+        assert_eq!(subtree_pids(&rows, 4), vec![50]);
+        assert!(subtree_pids(&rows, 9).is_empty());
+    }
+}
@@ -0,0 +1,88 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+use crate::filter::FilterMode;
+use super::keys::{self, Action};
+use super::{mouse, AppState};
+
+/// Width of the `[x] ` column at the start of each row.
+const CHECKBOX_WIDTH: u16 = 4;
+
+pub fn render(f: &mut Frame, area: Rect, state: &mut AppState) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(5), Constraint::Length(2)])
+        .split(area);
+
+    render_tree(f, chunks[0], state);
+    render_footer(f, chunks[1], state);
+}
+
+fn render_tree(f: &mut Frame, area: Rect, state: &mut AppState) {
+    let mode_str = match state.filter_mode {
+        FilterMode::Strict => "Strict: dev runtimes only",
+        FilterMode::Loose => "Loose: all listening processes",
+    };
+    let hints = keys::title_hints(&state.keymap, &[(&[Action::SwitchView], "port view"), (&[Action::SwitchFilter], "switch filter")]);
+    let title = format!(" Process Tree  [{}]  {} ", mode_str, hints);
+
+    let rows = state.tree_rows();
+    let items: Vec<ListItem> = rows
+        .iter()
+        .map(|row| {
+            let Some(p) = state.all_processes.iter().find(|p| p.pid == row.pid) else {
+                return ListItem::new(format!("{}{}", row.prefix, row.pid));
+            };
+            let fold = match (row.has_children, row.expanded) {
+                (false, _) => "  ",
+                (true, true) => "▾ ",
+                (true, false) => "▸ ",
+            };
+            let checkbox = if !state.tree_killable(row.pid) {
+                "    "
+            } else if state.is_checked(row.pid) {
+                "[x] "
+            } else {
+                "[ ] "
+            };
+            let ports = if p.ports.is_empty() {
+                String::new()
+            } else {
+                format!(" :{}", p.ports_display())
+            };
+            let style = if row.listed {
+                state.theme.accent.patch(state.theme.process(p, state.protect.is_protected(p)))
+            } else {
+                state.theme.muted
+            };
+            ListItem::new(Line::from(vec![
+                Span::raw(checkbox),
+                Span::styled(row.prefix.clone(), state.theme.muted),
+                Span::raw(fold),
+                Span::styled(format!("{} {}{}", p.pid, p.name_display(), ports), style),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(state.theme.selected);
+
+    let selected = if rows.is_empty() { None } else { Some(state.tree_selected) };
+    let mut list_state = ListState::default().with_selected(selected);
+    f.render_stateful_widget(list, area, &mut list_state);
+
+    let inner = Block::default().borders(Borders::ALL).inner(area);
+    state.hits.rows = Some((inner, list_state.offset()));
+    state.hits.row_count = rows.len();
+    state.hits.checkbox_width = CHECKBOX_WIDTH;
+}
+
+fn render_footer(f: &mut Frame, area: Rect, state: &mut AppState) {
+    let msg = mouse::footer(area, state);
+    let p = Paragraph::new(msg).style(state.theme.muted);
+    f.render_widget(p, area);
+}
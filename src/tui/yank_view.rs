@@ -0,0 +1,33 @@
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState},
+    Frame,
+};
+use super::{clipboard, keys, AppState};
+
+/// Yank menu: pick a field of the current process to copy.
+pub fn render(f: &mut Frame, area: Rect, state: &AppState) {
+    let (Some(selected), Some(p)) = (state.yank_menu, state.current_process()) else { return };
+
+    let items: Vec<ListItem> = clipboard::fields(p)
+        .into_iter()
+        .map(|(label, value)| {
+            ListItem::new(Line::from(vec![
+                Span::styled(format!(" {:<10}", label), state.theme.accent),
+                Span::raw(value),
+            ]))
+        })
+        .collect();
+
+    let height = (items.len() as u16 + 2).min(area.height);
+    let [popup] = Layout::horizontal([Constraint::Percentage(70)]).flex(Flex::Center).areas(area);
+    let [popup] = Layout::vertical([Constraint::Length(height)]).flex(Flex::Center).areas(popup);
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(keys::menu_title(&state.keymap, "Copy to clipboard", "copy")))
+        .highlight_style(state.theme.selected);
+    let mut list_state = ListState::default().with_selected(Some(selected));
+    f.render_widget(Clear, popup);
+    f.render_stateful_widget(list, popup, &mut list_state);
+}
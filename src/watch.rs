@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+use std::thread;
+use std::time::Duration;
+use crate::clock;
+use crate::process_info::ProcessInfo;
+
+#[derive(Debug)]
+pub enum Change {
+    Appeared(ProcessInfo),
+    Disappeared(ProcessInfo),
+    PortsChanged { process: ProcessInfo, old_ports: Vec<u16> },
+}
+
+impl std::fmt::Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Change::Appeared(p) => write!(
+                f,
+                "+ {:<8} {:<12} :{:<16} {}",
+                p.pid,
+                p.name,
+                p.ports_display(),
+                p.cmd.join(" ")
+            ),
+            Change::Disappeared(p) => write!(
+                f,
+                "- {:<8} {:<12} :{}",
+                p.pid,
+                p.name,
+                p.ports_display()
+            ),
+            Change::PortsChanged { process, old_ports } => write!(
+                f,
+                "~ {:<8} {:<12} :{} -> :{}",
+                process.pid,
+                process.name,
+                old_ports
+                    .iter()
+                    .map(|p| p.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                process.ports_display()
+            ),
+        }
+    }
+}
+
+/// Processes are keyed by PID and start time so a recycled PID counts as a new server.
+fn key(p: &ProcessInfo) -> (u32, u64) {
+    (p.pid, p.start_time_secs)
+}
+
+pub fn diff(prev: &[ProcessInfo], next: &[ProcessInfo]) -> Vec<Change> {
+    let prev_map: HashMap<(u32, u64), &ProcessInfo> = prev.iter().map(|p| (key(p), p)).collect();
+    let next_map: HashMap<(u32, u64), &ProcessInfo> = next.iter().map(|p| (key(p), p)).collect();
+
+    let mut changes = Vec::new();
+
+    for p in prev {
+        if !next_map.contains_key(&key(p)) {
+            changes.push(Change::Disappeared(p.clone()));
+        }
+    }
+
+    for p in next {
+        match prev_map.get(&key(p)) {
+            None => changes.push(Change::Appeared(p.clone())),
+            Some(old) => {
+                let mut old_ports = old.ports.clone();
+                let mut new_ports = p.ports.clone();
+                old_ports.sort_unstable();
+                new_ports.sort_unstable();
+                if old_ports != new_ports {
+                    changes.push(Change::PortsChanged {
+                        process: p.clone(),
+                        old_ports: old.ports.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    changes
+}
+
+/// Runs `collect` every `interval` and prints one timestamped line per change since the
+/// first scan, which is only the baseline. Never returns.
+pub fn run<F>(interval: Duration, mut collect: F)
+where
+    F: FnMut() -> Vec<ProcessInfo>,
+{
+    let mut prev = collect();
+    loop {
+        thread::sleep(interval);
+        let next = collect();
+        let stamp = clock::format_utc(clock::now_secs());
+        for change in diff(&prev, &next) {
+            println!("{}  {}", stamp, change);
+        }
+        prev = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_proc(pid: u32, ports: Vec<u16>) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            name: "node".to_string(),
+            cmd: vec!["node".to_string(), "server.js".to_string()],
+            ports,
+            start_time_secs: 1000,
+            memory_kb: 0,
+            parent_pid: None,
+            parent_name: None,
+            is_dev_runtime: true,
+            score: 0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_diff_no_changes() {
+        let prev = vec![make_proc(1, vec![3000])];
+        let next = vec![make_proc(1, vec![3000])];
+        assert!(diff(&prev, &next).is_empty());
+    }
+
+    #[test]
+    fn test_diff_appeared_and_disappeared() {
+        let prev = vec![make_proc(1, vec![3000])];
+        let next = vec![make_proc(2, vec![5173])];
+        let changes = diff(&prev, &next);
+        assert_eq!(changes.len(), 2);
+        assert!(matches!(&changes[0], Change::Disappeared(p) if p.pid == 1));
+        assert!(matches!(&changes[1], Change::Appeared(p) if p.pid == 2));
+    }
+
+    #[test]
+    fn test_diff_ports_changed() {
+        let prev = vec![make_proc(1, vec![3000])];
+        let next = vec![make_proc(1, vec![3001, 3000])];
+        let changes = diff(&prev, &next);
+        assert_eq!(changes.len(), 1);
+        assert!(matches!(&changes[0], Change::PortsChanged { old_ports, .. } if old_ports == &vec![3000]));
+        assert_eq!(changes[0].to_string(), "~ 1        node         :3000 -> :3001, 3000");
+    }
+
+    #[test]
+    fn test_diff_port_order_ignored() {
+        let prev = vec![make_proc(1, vec![3000, 3001])];
+        let next = vec![make_proc(1, vec![3001, 3000])];
+        assert!(diff(&prev, &next).is_empty());
+    }
+
+    #[test]
+    fn test_diff_recycled_pid() {
+        let prev = vec![make_proc(1, vec![3000])];
+        let mut reused = make_proc(1, vec![3000]);
+        reused.start_time_secs = 2000;
+        let changes = diff(&prev, &[reused]);
+        assert_eq!(changes.len(), 2);
+    }
+}